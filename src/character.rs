@@ -1,46 +1,310 @@
 use std::cmp;
 use colored::*;
 use serde::Deserialize;
-use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
+use serde::Serialize;
 use std::path::Path;
+use std::path::PathBuf;
+use serde_yaml::Mapping;
+use serde_yaml::Value;
 
 use crate::configs;
+use crate::error;
+use error::GameError;
+use error::GameResult;
 use crate::modifiers;
 use modifiers::Aura;
 use modifiers::Ability;
+use modifiers::Apply;
+use modifiers::Modifier;
+use modifiers::ModifierMode;
+use modifiers::PositionModifier;
+use modifiers::Statistic;
+use crate::items::Item;
+use crate::progression;
+use crate::recruitment::Rarity;
+use progression::StatGrowth;
+use crate::tags::Tag;
+use crate::targeting::Guard;
+use crate::validation;
+use validation::ValidationReport;
 
-#[derive(Debug, PartialEq, Deserialize)]
-pub struct Character {
-    pub name: String,
-    pub class: String,
+// Base statistics, before any modifiers (read the effective values through Character's getters)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
     pub speed: i32,
-    pub health: i32,
     pub health_max: i32,
     pub power: i32,
     pub critical_chance: f64,
-    pub attack_type: String,
     pub dt: i32, // damage threshold (flat amount of damage ignored)
-    pub dr: f64, // damage reduction (percent taken off the top)
-    pub tags: Vec<String>, // modifiers/properties characters can possess
+    pub dr: f64 // damage reduction (percent taken off the top)
+}
+
+const FORMATION_SOURCE: &str = "formation";
+pub const ABILITY_SOURCE_PREFIX: &str = "ability: "; // followed by the name of the character using it
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Character {
+    pub name: String,
+    pub class: String,
+    pub health: i32, // current health
+    #[serde(flatten)]
+    pub base: Stats,
+    pub attack_type: String,
+    #[serde(default)]
+    pub tags: Vec<Tag>, // traits the character has (see tags.rs)
     pub auras: Vec<Aura>,
     pub abilities: Vec<Ability>,
-    xp: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guard: Option<Guard>, // guardians can intercept single-target attacks aimed at their ward
+    #[serde(default)]
+    pub formation: Vec<PositionModifier>, // bonuses that depend on the character's position
+    #[serde(skip)]
+    pub squad_formation: Vec<PositionModifier>, // the squad's formation bonuses (saved with the squad)
+    #[serde(default)]
+    pub equipment: Vec<String>, // item files to equip when the character is loaded
+    #[serde(skip)]
+    pub items: Vec<Item>, // equipped items
+    #[serde(skip)]
+    item_tags: Vec<Tag>, // tags granted by items (tags the character already had aren't counted)
+    #[serde(skip)]
+    modifiers: Vec<Modifier>, // active changes to the base statistics
+    xp: i32, // experience towards the next level
     pub level: u32,
+    pub cost: u32, // recruitment cost, in points (required, so no character is free by accident)
+    #[serde(default)]
+    pub rarity: Rarity,
+    #[serde(skip)]
+    pub kills: u32, // enemies defeated this battle
+    #[serde(skip)]
+    pub roster_id: Option<String>, // the roster unit this character was built from, if any
     pub description: String
 }
 
-// Read a character from file
-pub fn read_params_from_file<P: AsRef<Path>>(path: P) -> Result<Character, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let params = serde_yaml::from_reader(reader)?;
-    Ok(params)
+// The fields a character file may set, all optional since its class template can fill them in.
+// Merging with the template (and flattening the statistics) loses line and column numbers, so files
+// are checked against this first to report mistakes like `power: "lots"` where they are.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+#[allow(dead_code)] // Only parsed, never read
+struct CharacterFields {
+    name: Option<String>,
+    class: Option<String>,
+    health: Option<i32>,
+    speed: Option<i32>,
+    health_max: Option<i32>,
+    power: Option<i32>,
+    critical_chance: Option<f64>,
+    dt: Option<i32>,
+    dr: Option<f64>,
+    attack_type: Option<String>,
+    tags: Option<Vec<Tag>>,
+    auras: Option<Vec<Aura>>,
+    abilities: Option<Vec<Ability>>,
+    guard: Option<Guard>,
+    formation: Option<Vec<PositionModifier>>,
+    equipment: Option<Vec<String>>,
+    xp: Option<i32>,
+    level: Option<u32>,
+    cost: Option<u32>,
+    rarity: Option<Rarity>,
+    description: Option<String>
+}
+
+// Read a character from file, filling in anything it leaves out from its class template
+pub fn read_params_from_file(path: &str) -> GameResult<Character> {
+    error::read_yaml_file::<CharacterFields>(path)?;
+    let params: Value = error::read_yaml_file(path)?;
+    let params: Value = inherit_class(params, &classes_directory(Path::new(path)))?;
+    serde_yaml::from_value(params).map_err(|error| GameError::from_yaml(path, error))
+}
+
+// The other data folders sit next to the characters folder (data/characters/ => data/<folder>)
+pub fn data_directory(directory_characters: &Path, folder: &str) -> PathBuf {
+    directory_characters.parent().unwrap_or_else(|| Path::new("")).join(folder)
+}
+
+// Class templates: data/characters/X.yml => data/classes/
+fn classes_directory(character_path: &Path) -> PathBuf {
+    data_directory(character_path.parent().unwrap_or_else(|| Path::new("")), "classes")
+}
+
+// Overlay a character's fields on top of its class template (data/classes/<class>.yml, if any).
+// Fields are replaced as a whole, so a character listing abilities replaces the class abilities.
+// Current health defaults to maximum health.
+fn inherit_class(params: Value, directory_classes: &Path) -> GameResult<Value> {
+    let character_fields: Mapping = match params {
+        Value::Mapping(fields) => fields,
+        other => return Ok(other)
+    };
+    let mut fields: Mapping = Mapping::new();
+    if let Some(class) = character_fields.get(&Value::String("class".to_string())).and_then(Value::as_str) {
+        let template_path: PathBuf = directory_classes.join(format!("{}.yml", class));
+        if template_path.exists() {
+            let template: Value = error::read_yaml_file(&template_path.to_string_lossy())?;
+            if let Value::Mapping(template_fields) = template {
+                fields = template_fields;
+            }
+        }
+    }
+    for (key, value) in character_fields {
+        fields.insert(key, value);
+    }
+    let health: Value = Value::String("health".to_string());
+    if !fields.contains_key(&health) {
+        if let Some(health_max) = fields.get(&Value::String("health_max".to_string())).cloned() {
+            fields.insert(health, health_max);
+        }
+    }
+    Ok(Value::Mapping(fields))
+}
+
+// Show how modifiers changed a statistic, e.g. "6000 (5000 +20%)"
+fn describe_percent_change(effective: f64, base: f64, unit: &str) -> String {
+    if (effective - base).abs() < 1e-9 || base == 0.0 {
+        return format!("{}{}", effective.round(), unit);
+    }
+    let percent_change: f64 = (100.0 * (effective / base - 1.0)).round();
+    format!("{}{} ({}{} {:+}%)", effective.round(), unit, base.round(), unit, percent_change)
+}
+
+// Show how modifiers changed an additive statistic, e.g. "1 (-1 +2)"
+fn describe_flat_change(effective: i32, base: i32) -> String {
+    if effective == base {
+        return format!("{}", effective);
+    }
+    format!("{} ({} {:+})", effective, base, effective - base)
+}
+
+// Builds a character in code instead of from a data file. Anything left out keeps its default: a
+// level 1 character with 100 health, 10 power, 1 speed, no armor, and a single-target attack.
+pub struct CharacterBuilder {
+    character: Character
+}
+
+impl CharacterBuilder {
+    pub fn new(name: &str) -> CharacterBuilder {
+        CharacterBuilder {
+            character: Character {
+                name: name.to_string(),
+                class: String::new(),
+                health: 100,
+                base: Stats {speed: 1, health_max: 100, power: 10, critical_chance: 0.0, dt: 0, dr: 0.0},
+                attack_type: "single".to_string(),
+                tags: Vec::new(),
+                auras: Vec::new(),
+                abilities: Vec::new(),
+                guard: None,
+                formation: Vec::new(),
+                squad_formation: Vec::new(),
+                equipment: Vec::new(),
+                items: Vec::new(),
+                item_tags: Vec::new(),
+                modifiers: Vec::new(),
+                xp: 0,
+                level: 1,
+                cost: 0,
+                rarity: Rarity::Common,
+                kills: 0,
+                roster_id: None,
+                description: String::new()
+            }
+        }
+    }
+
+    pub fn class(mut self, class: &str) -> Self {
+        self.character.class = class.to_string();
+        self
+    }
+
+    pub fn speed(mut self, speed: i32) -> Self {
+        self.character.base.speed = speed;
+        self
+    }
+
+    // Sets maximum health (the character starts at full health)
+    pub fn health(mut self, health: i32) -> Self {
+        self.character.base.health_max = health;
+        self.character.health = health;
+        self
+    }
+
+    pub fn power(mut self, power: i32) -> Self {
+        self.character.base.power = power;
+        self
+    }
+
+    pub fn critical_chance(mut self, critical_chance: f64) -> Self {
+        self.character.base.critical_chance = critical_chance;
+        self
+    }
+
+    pub fn attack_type(mut self, attack_type: &str) -> Self {
+        self.character.attack_type = attack_type.to_string();
+        self
+    }
+
+    pub fn dt(mut self, dt: i32) -> Self {
+        self.character.base.dt = dt;
+        self
+    }
+
+    pub fn dr(mut self, dr: f64) -> Self {
+        self.character.base.dr = dr;
+        self
+    }
+
+    pub fn tags(mut self, tags: Vec<Tag>) -> Self {
+        self.character.tags = tags;
+        self
+    }
+
+    pub fn auras(mut self, auras: Vec<Aura>) -> Self {
+        self.character.auras = auras;
+        self
+    }
+
+    pub fn abilities(mut self, abilities: Vec<Ability>) -> Self {
+        self.character.abilities = abilities;
+        self
+    }
+
+    pub fn guard(mut self, guard: Guard) -> Self {
+        self.character.guard = Some(guard);
+        self
+    }
+
+    pub fn formation(mut self, formation: Vec<PositionModifier>) -> Self {
+        self.character.formation = formation;
+        self
+    }
+
+    pub fn level(mut self, level: u32) -> Self {
+        self.character.level = level;
+        self
+    }
+
+    pub fn cost(mut self, cost: u32) -> Self {
+        self.character.cost = cost;
+        self
+    }
+
+    pub fn rarity(mut self, rarity: Rarity) -> Self {
+        self.character.rarity = rarity;
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.character.description = description.to_string();
+        self
+    }
+
+    pub fn build(self) -> Character {
+        self.character
+    }
 }
 
 impl Character {
-    #[allow(dead_code)] // Used by tests
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: &str,
         class: &str,
@@ -51,42 +315,76 @@ impl Character {
         attack_type: &str,
         dt: i32,
         dr: f64,
-        tags: Vec<String>,
+        tags: Vec<Tag>,
         auras: Vec<Aura>,
         abilities: Vec<Ability>
     ) -> Character {
-        Character {
-            name: name.to_string(),
-            class: class.to_string(),
-            speed: speed,
-            health: health,
-            health_max: health,
-            power: power,
-            critical_chance: critical_chance,
-            attack_type: attack_type.to_string(),
-            dt: dt,
-            dr: dr,
-            tags: tags,
-            auras: auras,
-            abilities: abilities,
-            xp: 0,
-            level: 1,
-            description: "null".to_string()
-        }
+        Character::builder(name)
+            .class(class)
+            .speed(speed)
+            .health(health)
+            .power(power)
+            .critical_chance(critical_chance)
+            .attack_type(attack_type)
+            .dt(dt)
+            .dr(dr)
+            .tags(tags)
+            .auras(auras)
+            .abilities(abilities)
+            .build()
+    }
+
+    // Start building a character from defaults, e.g. Character::builder("Dave").health(20).dr(0.2).build()
+    pub fn builder(name: &str) -> CharacterBuilder {
+        CharacterBuilder::new(name)
+    }
+
+    pub fn new_from_file(filepath: &str) -> GameResult<Self> {
+        let mut character: Character = read_params_from_file(filepath)?;
+        character.validate_actor_data(filepath)?;
+        Ok(character)
     }
 
-    pub fn new_from_file(filepath: &str) -> Self {
-        let mut character: Character = read_params_from_file(filepath).unwrap();
-        Character::validate_actor_data(&mut character);
-        return character;
+    // Save the character in the format it's loaded from (level, experience, and current health
+    // included). Equipped items are saved by name only, so loading the file equips them again.
+    pub fn save_to_file(&self, filepath: &str) -> GameResult<()> {
+        error::write_yaml_file(filepath, &self.to_saved())
     }
 
-    // Validation
-    fn validate_actor_data(&mut self) {
-        if self.health > self.health_max {
-            println!("WARNING: data error in {}: current health ({}) > maximum health ({}). Overriding automatically.", self.name, self.health, self.health_max);
-            self.health = self.health_max;
+    // A copy of the character as it should be saved: without what its items added (modifiers aren't
+    // saved in any case), and with current health kept between 0 and the base maximum, so fallen
+    // characters load at 0 health and bonuses from auras and items aren't saved as health.
+    // Items append their auras and abilities, so the last matching copy of each is the item's.
+    fn to_saved(&self) -> Character {
+        let mut character: Character = self.clone();
+        character.health = character.health.clamp(0, character.base.health_max);
+        character.tags.retain(|tag| !self.item_tags.contains(tag));
+        for item in self.items.iter() {
+            for item_aura in item.auras.iter() {
+                if let Some(index) = character.auras.iter().rposition(|aura| aura == item_aura) {
+                    character.auras.remove(index);
+                }
+            }
+            for item_ability in item.abilities.iter() {
+                if let Some(index) = character.abilities.iter().rposition(|ability|
+                    ability.name == item_ability.name && ability.effects == item_ability.effects) {
+                    character.abilities.remove(index);
+                }
+            }
+        }
+        character.items.clear();
+        character.item_tags.clear();
+        character
+    }
+
+    // Validation: reports every problem in the file at once, fixing what it safely can
+    fn validate_actor_data(&mut self, filepath: &str) -> GameResult<()> {
+        let report: ValidationReport = validation::validate_character(self, filepath);
+        report.print_warnings();
+        if self.health > self.base.health_max {
+            self.health = self.base.health_max;
         }
+        report.into_result()
     }
 
     // Combat helper functions
@@ -94,15 +392,173 @@ impl Character {
     // Damage calculations (accounting for armor) => crit variable is passed through to print damage taken fn
     pub fn take_damage(&mut self, power: i32, crit: bool) {
         // Cap damage resistance at 80%
-        let effective_dr: f64 = self.dr.min(configs::MAXIUMUM_DAMAGE_RESIST);
+        let effective_dr: f64 = self.dr().min(configs::MAXIUMUM_DAMAGE_RESIST);
         // Apply damage reduction
         let reduced_damage: i32 = ((1.0 - effective_dr) * power as f64).round() as i32;
         // Apply damage threshold
-        let actual_damage: i32 = std::cmp::max(reduced_damage - self.dt, configs::MINIMUM_DAMAGE);
+        let actual_damage: i32 = std::cmp::max(reduced_damage - self.dt(), configs::MINIMUM_DAMAGE);
         self.health -= actual_damage;
         self.print_damage_taken(actual_damage, crit);
     }
 
+    // Damage that ignores damage threshold and resistance
+    pub fn take_direct_damage(&mut self, damage: i32) {
+        self.health -= damage;
+        self.print_damage_taken(damage, false);
+    }
+
+    // Restore current health, capped at maximum health (the dead are not revived)
+    pub fn heal(&mut self, amount: i32) {
+        if self.is_dead() {
+            return;
+        }
+        let healed: i32 = amount.max(0).min(self.health_max() - self.health);
+        self.health += healed;
+        if healed > 0 {
+            self.print_healed(healed);
+        }
+    }
+
+    // Effective statistics: base values with all active modifiers applied. Flat and percent of base
+    // modifiers are summed, percent of current modifiers compound on top, and the latest set-to wins.
+    // With configs::FLAT_MODIFIERS_FIRST off, flat modifiers are added after all percentages instead.
+    fn effective_value(&self, statistic: Statistic, base_value: f64) -> f64 {
+        let mut flat: f64 = 0.0;
+        let mut percent_of_base: f64 = 0.0;
+        let mut current_multiplier: f64 = 1.0;
+        let mut set_value: Option<f64> = None;
+        for modifier in self.modifiers.iter().filter(|modifier| modifier.statistic == statistic) {
+            match modifier.mode {
+                ModifierMode::Flat => flat += modifier.value,
+                ModifierMode::PercentOfBase => percent_of_base += modifier.value,
+                ModifierMode::PercentOfCurrent => current_multiplier *= 1.0 + modifier.value,
+                ModifierMode::Set => set_value = Some(modifier.value)
+            }
+        }
+        if let Some(value) = set_value {
+            return value;
+        }
+        let current_multiplier: f64 = current_multiplier.max(0.0);
+        if configs::FLAT_MODIFIERS_FIRST {
+            (base_value + flat + base_value * percent_of_base) * current_multiplier
+        } else {
+            (base_value + base_value * percent_of_base) * current_multiplier + flat
+        }
+    }
+
+    pub fn speed(&self) -> i32 {
+        self.effective_value(Statistic::Speed, self.base.speed as f64).round() as i32
+    }
+
+    pub fn health_max(&self) -> i32 {
+        let health_max: f64 = self.effective_value(Statistic::Health, self.base.health_max as f64).round();
+        health_max.clamp(1.0, configs::MAXIMUM_HEALTH as f64) as i32
+    }
+
+    // Modifiers can't drain power below the minimum (but a lower base value is left alone)
+    pub fn power(&self) -> i32 {
+        let power: i32 = self.effective_value(Statistic::Power, self.base.power as f64).round() as i32;
+        power.max(self.base.power.min(configs::MINIMUM_POWER))
+    }
+
+    pub fn critical_chance(&self) -> f64 {
+        self.effective_value(Statistic::CriticalChance, self.base.critical_chance).clamp(0.0, configs::CRITICAL_CHANCE_CAP)
+    }
+
+    pub fn dt(&self) -> i32 {
+        (self.effective_value(Statistic::Dt, self.base.dt as f64).round() as i32).max(0)
+    }
+
+    pub fn dr(&self) -> f64 {
+        self.effective_value(Statistic::Dr, self.base.dr).clamp(0.0, configs::MAXIUMUM_DAMAGE_RESIST)
+    }
+
+    pub fn modifiers(&self) -> &Vec<Modifier> {
+        &self.modifiers
+    }
+
+    // Stat modification: the single entry point for auras, abilities, and position modifiers.
+    // Changes to maximum health shift current health by the same amount, unless the character is dead (no reviving)
+    pub fn add_modifier(&mut self, modifier: Modifier) {
+        let health_max_before: i32 = self.health_max();
+        self.modifiers.push(modifier);
+        if !self.is_dead() {
+            self.health += self.health_max() - health_max_before;
+        }
+    }
+
+    pub fn apply_modifier<M: Apply>(&mut self, statistic: Statistic, modifier: &M, source: &str) {
+        self.add_modifier(modifier.to_modifier(statistic, source));
+    }
+
+    // Remove every modifier granted by a source (damage taken in the meantime is kept)
+    pub fn remove_modifiers_from(&mut self, source: &str) {
+        self.modifiers.retain(|modifier| modifier.source != source);
+        self.health = self.health.min(self.health_max());
+    }
+
+    // Count down modifier durations at the end of a round and drop the ones that ran out
+    pub fn tick_modifiers(&mut self) {
+        for modifier in self.modifiers.iter_mut() {
+            if let Some(rounds) = modifier.duration {
+                modifier.duration = Some(rounds.saturating_sub(1));
+            }
+        }
+        self.modifiers.retain(|modifier| modifier.duration != Some(0));
+        self.health = self.health.min(self.health_max());
+    }
+
+    // Kills, ability activations, and ability modifiers last one battle (injuries and items stay)
+    pub fn reset_battle_counters(&mut self) {
+        self.kills = 0;
+        for ability in self.abilities.iter_mut() {
+            ability.activations = 0;
+        }
+        self.modifiers.retain(|modifier| !modifier.source.starts_with(ABILITY_SOURCE_PREFIX));
+        self.health = self.health.min(self.health_max());
+    }
+
+    // Position modifiers: remove any bonuses from a previous position, then apply those active at
+    // the new position
+    pub fn apply_formation(&mut self, position: usize) {
+        self.clear_formation();
+        let active_modifiers: Vec<PositionModifier> = self.formation.iter().chain(self.squad_formation.iter())
+            .filter(|modifier| modifier.applies_at(position))
+            .cloned()
+            .collect();
+        for modifier in active_modifiers.iter() {
+            self.apply_modifier(modifier.statistic, modifier, FORMATION_SOURCE);
+        }
+    }
+
+    pub fn clear_formation(&mut self) {
+        self.remove_modifiers_from(FORMATION_SOURCE);
+    }
+
+    // Equip an item: its bonuses become modifiers, and its tags, auras, and abilities are added to the
+    // character's own. Returns false (and leaves the character alone) if the slot is already taken.
+    pub fn equip(&mut self, item: Item) -> bool {
+        if let Some(equipped) = self.items.iter().find(|equipped| equipped.slot == item.slot) {
+            println!("WARNING: {} already has {} equipped in the {:?} slot, ignoring {}.", self.name, equipped.name,
+                item.slot, item.name);
+            return false;
+        }
+        let source: String = format!("item: {}", item.name);
+        for bonus in item.bonuses.iter() {
+            self.apply_modifier(bonus.statistic, bonus, &source);
+        }
+        for tag in item.tags.iter() {
+            if !self.tags.contains(tag) {
+                self.tags.push(*tag);
+                self.item_tags.push(*tag);
+            }
+        }
+        self.auras.extend(item.auras.iter().cloned());
+        self.abilities.extend(item.abilities.iter().cloned());
+        self.items.push(item);
+        true
+    }
+
     // Check if character died
     pub fn is_dead(&self) -> bool {
         if self.health <= 0 {
@@ -113,11 +569,50 @@ impl Character {
     }
 
     // Levelling
-    #[allow(dead_code)]
-    pub fn add_xp(&mut self, xp_gained: i32) {
-        self.xp += xp_gained;
-        if self.xp > 10 {
-            self.level += 1;
+    pub fn xp(&self) -> i32 {
+        self.xp
+    }
+
+    // Add experience and level up as many times as it allows (experience stops accruing at the level
+    // cap); returns the number of levels gained
+    pub fn add_xp(&mut self, xp_gained: i32, growth: &StatGrowth) -> u32 {
+        let mut levels_gained: u32 = 0;
+        self.xp += xp_gained.max(0);
+        while self.level < configs::LEVEL_CAP && self.xp >= progression::xp_to_next_level(self.level) {
+            self.xp -= progression::xp_to_next_level(self.level);
+            self.level_up(growth);
+            levels_gained += 1;
+        }
+        if self.level >= configs::LEVEL_CAP {
+            self.xp = 0;
+        }
+        levels_gained
+    }
+
+    // Bring a freshly loaded character up to a saved level and experience (without announcing each
+    // level up)
+    pub fn restore_progress(&mut self, level: u32, xp: i32, growth: &StatGrowth) {
+        while self.level < level.min(configs::LEVEL_CAP) {
+            self.grow(growth);
+        }
+        self.xp = xp;
+    }
+
+    fn level_up(&mut self, growth: &StatGrowth) {
+        self.grow(growth);
+        self.print_level_up();
+    }
+
+    fn grow(&mut self, growth: &StatGrowth) {
+        self.level += 1;
+        self.base.speed += growth.speed;
+        self.base.health_max += growth.health_max;
+        self.base.power += growth.power;
+        self.base.critical_chance += growth.critical_chance;
+        self.base.dt += growth.dt;
+        self.base.dr += growth.dr;
+        if !self.is_dead() {
+            self.health += growth.health_max;
         }
     }
 
@@ -131,9 +626,15 @@ impl Character {
     pub fn print_pretty_stats(&self) {
         println!("--------------------");
         println!("Name: {}", self.name);
-        println!("Health: {}", self.health);
-        println!("Power: {} | Crit. Chance: {}%", self.power, 100.0 * self.critical_chance);
-        println!("Speed: {}", self.speed);
+        println!("Health: {}/{}", self.health, describe_percent_change(self.health_max() as f64, self.base.health_max as f64, ""));
+        println!("Power: {} | Crit. Chance: {}", describe_percent_change(self.power() as f64, self.base.power as f64, ""),
+            describe_percent_change(100.0 * self.critical_chance(), 100.0 * self.base.critical_chance, "%"));
+        println!("Speed: {}", describe_flat_change(self.speed(), self.base.speed));
+        println!("Cost: {} points ({})", self.cost, self.rarity);
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self.tags.iter().map(Tag::describe).collect();
+            println!("Tags: {}", tags.join(", "));
+        }
         println!("{}", self.description);
         println!("--------------------\n");
     }
@@ -146,7 +647,7 @@ impl Character {
 
     fn print_damage_taken(&self, damage: i32, is_crit: bool) {
         let health_rounded: i32 = cmp::max(self.health, 0);
-        let health_percent: i32 = (100.0 * (health_rounded as f64 / self.health_max as f64)).round() as i32;
+        let health_percent: i32 = (100.0 * (health_rounded as f64 / self.health_max() as f64)).round() as i32;
         if is_crit {
             if health_percent >= configs::HEALTH_LEVEL_GREEN {
                 println!("{}! {} took {} points of damage ({}% HP remaining).", "Critical hit".to_string().bold(), self.name, damage.to_string().bold(), health_percent.to_string().green());
@@ -166,6 +667,33 @@ impl Character {
         }
     }
 
+    fn print_level_up(&self) {
+        let output = format!("{} reached level {}!", self.name, self.level);
+        println!("{}", output.bold());
+    }
+
+    pub fn print_used_ability(&self, ability_name: &str) {
+        println!("{} used {}!", self.name, ability_name.bold());
+    }
+
+    fn print_healed(&self, amount: i32) {
+        println!("{} recovers {} health.", self.name, amount.to_string().green());
+    }
+
+    pub fn print_intercepting(&self, ward_name: &str) {
+        // No newline b/c chained with print_damage_taken function
+        print!("{} intercepts the attack on {}! ", self.name.bold(), ward_name);
+    }
+
+    pub fn print_moved(&self, destination: &str) {
+        println!("{} moves to the {}!", self.name, destination);
+    }
+
+    pub fn print_entered(&self) {
+        let output = format!("{} enters the battle!", self.name);
+        println!("{}", output.bold());
+    }
+
     pub fn print_died(&self) {
         let output = format!("{} died!", self.name);
         println!("{}", output.red());
@@ -178,10 +706,17 @@ impl Character {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::items;
+    use crate::items::Slot;
+    use crate::items::StatBonus;
+    use crate::modifiers::AbilityEffect;
+    use crate::modifiers::AbilityTarget;
+    use crate::modifiers::AuraTarget;
+    use crate::modifiers::Placement;
 
     // Character Struct tests
     #[test]
-    fn test_new() {
+    fn test_builder() {
         const EXPECTED_NAME: &str = "Dave";
         const EXPECTED_CLASS: &str = "Warrior";
         const EXPECTED_SPEED: i32 = 1;
@@ -191,148 +726,606 @@ mod tests {
         let expected_attack_type: String = "single".to_string();
         const EXPECTED_DT: i32 = 0;
         const EXPECTED_DR: f64 = 0.0;
-        let input_tags: Vec<String> = vec!["null".to_string()];
-        let input_auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let input_abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
-        let expected_tags: Vec<String> = vec!["null".to_string()];
-        let expected_auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let expected_abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
+        let input_tags: Vec<Tag> = Vec::new();
+        let input_auras: Vec<Aura> = vec![Aura::new(Statistic::Health, AuraTarget::Allies, 0.1)];
+        let input_abilities: Vec<Ability> = vec![Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack")];
+        let expected_tags: Vec<Tag> = Vec::new();
+        let expected_auras: Vec<Aura> = vec![Aura::new(Statistic::Health, AuraTarget::Allies, 0.1)];
+        let expected_abilities: Vec<Ability> = vec![Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack")];
 
-        let test_character = Character::new(EXPECTED_NAME, EXPECTED_CLASS, EXPECTED_SPEED,
-            EXPECTED_HEALTH, EXPECTED_POWER, EXPECTED_CRIT, &expected_attack_type, EXPECTED_DT,
-            EXPECTED_DR, input_tags, input_auras, input_abilities);
+        let test_character = Character::builder(EXPECTED_NAME).class(EXPECTED_CLASS).speed(EXPECTED_SPEED)
+            .health(EXPECTED_HEALTH).power(EXPECTED_POWER).critical_chance(EXPECTED_CRIT)
+            .attack_type(&expected_attack_type).dt(EXPECTED_DT).dr(EXPECTED_DR).tags(input_tags).auras(input_auras)
+            .abilities(input_abilities).build();
 
         assert_eq!(test_character.name, EXPECTED_NAME);
         assert_eq!(test_character.class, EXPECTED_CLASS);
-        assert_eq!(test_character.speed, EXPECTED_SPEED);
+        assert_eq!(test_character.speed(), EXPECTED_SPEED);
         assert_eq!(test_character.health, EXPECTED_HEALTH);
-        assert_eq!(test_character.power, EXPECTED_POWER);
-        assert_eq!(test_character.critical_chance, EXPECTED_CRIT);
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert_eq!(test_character.critical_chance(), EXPECTED_CRIT);
         assert_eq!(test_character.attack_type, expected_attack_type);
         assert_eq!(test_character.tags, expected_tags);
         assert_eq!(test_character.auras, expected_auras);
         assert_eq!(test_character.abilities, expected_abilities);
     }
 
+    #[test]
+    fn test_builder_defaults() {
+        const EXPECTED_HEALTH: i32 = 100;
+        const EXPECTED_POWER: i32 = 10;
+        const EXPECTED_SPEED: i32 = 1;
+        const EXPECTED_LEVEL: u32 = 1;
+
+        let test_character = Character::builder("Dave").build();
+
+        assert_eq!(test_character.name, "Dave");
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+        assert_eq!(test_character.health_max(), EXPECTED_HEALTH);
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert_eq!(test_character.speed(), EXPECTED_SPEED);
+        assert_eq!(test_character.attack_type, "single");
+        assert_eq!(test_character.level, EXPECTED_LEVEL);
+        assert!(test_character.tags.is_empty());
+    }
+
+    #[test]
+    fn test_new_matches_builder() {
+        let expected_character = Character::builder("Dave").class("Warrior").speed(2).health(10).power(5)
+            .critical_chance(0.25).attack_type("all").dt(3).dr(0.1).tags(vec![Tag::Undead]).build();
+
+        let test_character = Character::new("Dave", "Warrior", 2, 10, 5, 0.25, "all", 3, 0.1, vec![Tag::Undead],
+            Vec::new(), Vec::new());
+
+        assert_eq!(test_character, expected_character);
+    }
+
     #[test]
     fn test_take_damage_no_armor() {
-        const NAME: &str = "Dave";
-        const CLASS: &str = "Warrior";
-        const SPEED: i32 = 1;
-        const HEALTH: i32 = 20;
-        const POWER: i32 = 5;
         const CRIT: bool = false;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 0;
-        const DR: f64 = 0.0;
-        let tags: Vec<String> = vec!["null".to_string()];
-        let auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         const INCOMING_DAMAGE: i32 = 10;
         const EXPECTED_HEALTH: i32 = 10;
 
-        let mut test_character = Character::new(NAME, CLASS, SPEED, HEALTH, POWER, CRITICAL_CHANCE,
-            &attack_type, DT, DR, tags, auras, abilities);
+        let mut test_character = Character::builder("Dave").health(20).build();
         test_character.take_damage(INCOMING_DAMAGE, CRIT);
         assert_eq!(test_character.health, EXPECTED_HEALTH);
     }
 
+    // Class template tests
+    #[test]
+    fn test_new_from_file_inherits_class() {
+        let test_character = Character::new_from_file("data/characters/EvilSwordsman.yml").unwrap();
+        const EXPECTED_SPEED: i32 = -1; // from the Warrior template
+        const EXPECTED_POWER: i32 = 8000; // from the Warrior template
+        const EXPECTED_HEALTH: i32 = 75000; // overridden, and current health follows maximum health
+        let expected_attack_type: String = "single".to_string();
+
+        assert_eq!(test_character.base.speed, EXPECTED_SPEED);
+        assert_eq!(test_character.base.power, EXPECTED_POWER);
+        assert_eq!(test_character.base.health_max, EXPECTED_HEALTH);
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+        assert_eq!(test_character.attack_type, expected_attack_type);
+        assert_eq!(test_character.auras.len(), 1);
+        assert!(test_character.abilities.is_empty());
+    }
+
+    #[test]
+    fn test_new_from_file_locates_bad_field() {
+        let directory: PathBuf = std::env::temp_dir().join("test_new_from_file_locates_bad_field");
+        std::fs::create_dir_all(directory.join("characters")).unwrap();
+        std::fs::create_dir_all(directory.join("classes")).unwrap();
+        std::fs::copy("data/classes/Warrior.yml", directory.join("classes").join("Warrior.yml")).unwrap();
+        let path: PathBuf = directory.join("characters").join("Dave.yml");
+        let bad_files: [(&str, usize); 2] = [
+            ("name: \"Dave\"\nclass: \"Warrior\"\npower: \"lots\"\ndescription: \"\"\n", 3),
+            ("name: \"Dave\"\nclass: \"Warrior\"\nauras:\n  - statistic: \"crit chance\"\n    target: \"allies\"\n    value: 0.1\n", 4)];
+
+        for (text, expected_line) in bad_files.iter() {
+            std::fs::write(&path, text).unwrap();
+            match Character::new_from_file(&path.to_string_lossy()) {
+                Err(GameError::Parse {line, ..}) => assert_eq!(line, Some(*expected_line)),
+                other => panic!("expected a parse error, got {:?}", other.map(|character| character.name))
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_from_file_requires_cost() {
+        let directory: PathBuf = std::env::temp_dir().join("test_new_from_file_requires_cost");
+        std::fs::create_dir_all(directory.join("characters")).unwrap();
+        std::fs::create_dir_all(directory.join("classes")).unwrap();
+        std::fs::copy("data/classes/Warrior.yml", directory.join("classes").join("Warrior.yml")).unwrap();
+        let path: PathBuf = directory.join("characters").join("Dave.yml");
+        let text: String = std::fs::read_to_string("data/characters/GoodSwordsman.yml").unwrap().lines()
+            .filter(|line| !line.starts_with("cost:"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        std::fs::write(&path, text).unwrap();
+
+        match Character::new_from_file(&path.to_string_lossy()) {
+            Err(GameError::Parse {message, ..}) => assert!(message.contains("cost")),
+            other => panic!("expected a parse error, got {:?}", other.map(|character| character.name))
+        }
+    }
+
+    #[test]
+    fn test_inherit_class_overrides() {
+        let params: Value = serde_yaml::from_str("name: \"Dave\"\nclass: \"Mage\"\npower: 1\nhealth: 10").unwrap();
+
+        let merged: Value = inherit_class(params, Path::new("data/classes")).unwrap();
+
+        assert_eq!(merged["power"], Value::from(1));
+        assert_eq!(merged["health"], Value::from(10));
+        assert_eq!(merged["attack_type"], Value::from("all"));
+    }
+
+    #[test]
+    fn test_inherit_class_without_template() {
+        let params: Value = serde_yaml::from_str("name: \"Smaug\"\nclass: \"Creature\"\nhealth_max: 10").unwrap();
+        let expected: Value = serde_yaml::from_str("name: \"Smaug\"\nclass: \"Creature\"\nhealth_max: 10\nhealth: 10").unwrap();
+
+        assert_eq!(inherit_class(params, Path::new("data/classes")).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_new_from_file_missing() {
+        let result = Character::new_from_file("data/characters/Nobody.yml");
+
+        assert_eq!(result, Err(GameError::MissingFile {path: "data/characters/Nobody.yml".to_string()}));
+    }
+
+    #[test]
+    fn test_validate_actor_data() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(1.5).build();
+
+        assert!(test_character.validate_actor_data("Dave.yml").is_err());
+        test_character.base.critical_chance = 0.5;
+        test_character.health = 200;
+        assert_eq!(test_character.validate_actor_data("Dave.yml"), Ok(()));
+        assert_eq!(test_character.health, 100);
+    }
+
+    #[test]
+    fn test_data_directory() {
+        assert_eq!(data_directory(Path::new("data/characters/"), "items"), PathBuf::from("data/items"));
+        assert_eq!(data_directory(Path::new("data/characters"), "items"), PathBuf::from("data/items"));
+    }
+
+    #[test]
+    fn test_classes_directory() {
+        assert_eq!(classes_directory(Path::new("data/characters/GoodTank.yml")), PathBuf::from("data/classes"));
+    }
+
+    // Saving tests
+    #[test]
+    fn test_serialize_round_trip_data_files() {
+        for entry in std::fs::read_dir("data/characters").unwrap() {
+            let path: String = entry.unwrap().path().to_string_lossy().to_string();
+            let character: Character = Character::new_from_file(&path).unwrap();
+
+            let saved: String = serde_yaml::to_string(&character).unwrap();
+            let loaded: Character = serde_yaml::from_str(&saved).unwrap();
+
+            assert_eq!(loaded, character, "{} changed after saving", path);
+        }
+    }
+
+    #[test]
+    fn test_save_leveled_equipped_character() {
+        let path = std::env::temp_dir().join("test_save_leveled_equipped_character.yml");
+        let path: &str = &path.to_string_lossy();
+        let mut character: Character = Character::new_from_file("data/characters/GoodTank.yml").unwrap();
+        items::equip_from_directory(&mut character, "data/items/").unwrap();
+        character.add_xp(progression::xp_to_next_level(1), &StatGrowth {power: 100, ..StatGrowth::default()});
+        const EXPECTED_LEVEL: u32 = 2;
+
+        assert_eq!(character.save_to_file(path), Ok(()));
+        let mut loaded: Character = Character::new_from_file(path).unwrap();
+        items::equip_from_directory(&mut loaded, "data/items/").unwrap();
+
+        assert_eq!(loaded.level, EXPECTED_LEVEL);
+        assert_eq!(loaded, character);
+    }
+
+    #[test]
+    fn test_save_keeps_native_tags_and_auras() {
+        let path = std::env::temp_dir().join("test_save_keeps_native_tags_and_auras.yml");
+        let path: &str = &path.to_string_lossy();
+        let aura = Aura::new(Statistic::Speed, AuraTarget::Allies, 1.0);
+        let mut test_character = Character::builder("Dave").tags(vec![Tag::Taunt]).auras(vec![aura.clone()]).build();
+        let mut shield = Item::new("Shield", Slot::Armor);
+        shield.tags = vec![Tag::Taunt, Tag::Stealth];
+        shield.auras = vec![aura.clone()];
+        test_character.equip(shield);
+
+        assert_eq!(test_character.save_to_file(path), Ok(()));
+        let loaded: Character = read_params_from_file(path).unwrap();
+
+        assert_eq!(loaded.tags, vec![Tag::Taunt]);
+        assert_eq!(loaded.auras, vec![aura]);
+    }
+
+    #[test]
+    fn test_save_dead_character() {
+        let path = std::env::temp_dir().join("test_save_dead_character.yml");
+        let path: &str = &path.to_string_lossy();
+        let mut test_character = Character::builder("Dave").class("Warrior").health(100).build();
+        test_character.add_xp(progression::xp_to_next_level(1), &StatGrowth {health_max: 10, ..StatGrowth::default()});
+        test_character.take_damage(400, false);
+        const EXPECTED_LEVEL: u32 = 2;
+        const EXPECTED_HEALTH: i32 = 0;
+
+        assert_eq!(test_character.save_to_file(path), Ok(()));
+        let loaded: Character = Character::new_from_file(path).unwrap();
+
+        assert_eq!(loaded.level, EXPECTED_LEVEL);
+        assert_eq!(loaded.health, EXPECTED_HEALTH);
+        assert!(loaded.is_dead());
+    }
+
+    // Equipment tests
+    #[test]
+    fn test_equip() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        let mut sword = Item::new("Sword", Slot::Weapon);
+        sword.bonuses = vec![StatBonus::new(Statistic::Power, 0.2)];
+        sword.tags = vec![Tag::Flanker];
+        sword.auras = vec![Aura::new(Statistic::Speed, AuraTarget::Allies, 1.0)];
+        const EXPECTED_POWER: i32 = 60;
+
+        assert!(test_character.equip(sword));
+        assert!(!test_character.equip(Item::new("Axe", Slot::Weapon)));
+
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert_eq!(test_character.tags, vec![Tag::Flanker]);
+        assert_eq!(test_character.auras.len(), 1);
+        assert_eq!(test_character.items.len(), 1);
+    }
+
+    // Levelling tests
+    #[test]
+    fn test_add_xp_level_up() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        test_character.health = 80;
+        let growth = StatGrowth {health_max: 10, power: 5, ..StatGrowth::default()};
+        const EXPECTED_LEVEL: u32 = 2;
+        const EXPECTED_XP: i32 = 10;
+        const EXPECTED_HEALTH: i32 = 90;
+        const EXPECTED_HEALTH_MAX: i32 = 110;
+        const EXPECTED_POWER: i32 = 55;
+
+        let levels_gained: u32 = test_character.add_xp(progression::xp_to_next_level(1) + EXPECTED_XP, &growth);
+
+        assert_eq!(levels_gained, 1);
+        assert_eq!(test_character.level, EXPECTED_LEVEL);
+        assert_eq!(test_character.xp(), EXPECTED_XP);
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+        assert_eq!(test_character.health_max(), EXPECTED_HEALTH_MAX);
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+    }
+
+    #[test]
+    fn test_add_xp_multiple_levels() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        let xp_gained: i32 = progression::xp_to_next_level(1) + progression::xp_to_next_level(2);
+
+        let levels_gained: u32 = test_character.add_xp(xp_gained, &StatGrowth::default());
+
+        assert_eq!(levels_gained, 2);
+        assert_eq!(test_character.level, 3);
+        assert_eq!(test_character.xp(), 0);
+    }
+
+    #[test]
+    fn test_restore_progress() {
+        let mut test_character = Character::builder("Dave").health(100).power(50).build();
+        let growth = StatGrowth {health_max: 10, power: 5, ..StatGrowth::default()};
+        const EXPECTED_LEVEL: u32 = 3;
+        const EXPECTED_XP: i32 = 40;
+        const EXPECTED_HEALTH: i32 = 120;
+        const EXPECTED_POWER: i32 = 60;
+
+        test_character.restore_progress(EXPECTED_LEVEL, EXPECTED_XP, &growth);
+
+        assert_eq!(test_character.level, EXPECTED_LEVEL);
+        assert_eq!(test_character.xp(), EXPECTED_XP);
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+    }
+
+    #[test]
+    fn test_add_xp_level_cap() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        test_character.level = configs::LEVEL_CAP - 1;
+
+        let levels_gained: u32 = test_character.add_xp(i32::MAX / 2, &StatGrowth::default());
+
+        assert_eq!(levels_gained, 1);
+        assert_eq!(test_character.level, configs::LEVEL_CAP);
+        assert_eq!(test_character.xp(), 0);
+        assert_eq!(test_character.add_xp(1000, &StatGrowth::default()), 0);
+    }
+
+    #[test]
+    fn test_take_direct_damage() {
+        let mut test_character = Character::builder("Dave").class("Warrior").health(20).power(5).dt(5).dr(0.5).build();
+        const EXPECTED_HEALTH: i32 = 8;
+
+        test_character.take_direct_damage(12);
+
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+    }
+
+    #[test]
+    fn test_heal_capped() {
+        let mut test_character = Character::builder("Dave").class("Warrior").health(20).power(5).build();
+        test_character.health = 15;
+        const EXPECTED_HEALTH: i32 = 20;
+
+        test_character.heal(10);
+
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+    }
+
+    #[test]
+    fn test_heal_does_not_revive() {
+        let mut test_character = Character::builder("Dave").class("Warrior").health(20).power(5).build();
+        test_character.health = 0;
+
+        test_character.heal(10);
+
+        assert!(test_character.is_dead());
+    }
+
     #[test]
     fn test_take_damage_dt_only() {
-        const NAME: &str = "Dave";
-        const CLASS: &str = "Warrior";
-        const SPEED: i32 = 1;
-        const HEALTH: i32 = 20;
-        const POWER: i32 = 5;
         const CRIT: bool = false;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 1;
-        const DR: f64 = 0.0;
-        let tags: Vec<String> = vec!["null".to_string()];
-        let auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         const INCOMING_DAMAGE: i32 = 15;
         const EXPECTED_HEALTH: i32 = 6;
 
-        let mut test_character = Character::new(NAME, CLASS, SPEED, HEALTH, POWER, CRITICAL_CHANCE,
-            &attack_type, DT, DR, tags, auras, abilities);
+        let mut test_character = Character::builder("Dave").health(20).dt(1).build();
         test_character.take_damage(INCOMING_DAMAGE, CRIT);
         assert_eq!(test_character.health, EXPECTED_HEALTH);
     }
 
     #[test]
     fn test_take_damage_dr_only() {
-        const NAME: &str = "Dave";
-        const CLASS: &str = "Warrior";
-        const SPEED: i32 = 1;
-        const HEALTH: i32 = 100;
-        const POWER: i32 = 5;
         const CRIT: bool = false;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 0;
-        const DR: f64 = 0.2;
-        let tags: Vec<String> = vec!["null".to_string()];
-        let auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         const INCOMING_DAMAGE: i32 = 50;
         const EXPECTED_HEALTH: i32 = 60;
 
-        let mut test_character = Character::new(NAME, CLASS, SPEED, HEALTH, POWER, CRITICAL_CHANCE,
-            &attack_type, DT, DR, tags, auras, abilities);
+        let mut test_character = Character::builder("Dave").health(100).dr(0.2).build();
         test_character.take_damage(INCOMING_DAMAGE, CRIT);
         assert_eq!(test_character.health, EXPECTED_HEALTH);
     }
 
     #[test]
     fn test_take_damage_dt_and_dr() {
-        const NAME: &str = "Dave";
-        const CLASS: &str = "Warrior";
-        const SPEED: i32 = 1;
-        const HEALTH: i32 = 100;
-        const POWER: i32 = 5;
         const CRIT: bool = false;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 1;
-        const DR: f64 = 0.2;
-        let tags: Vec<String> = vec!["null".to_string()];
-        let auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         const INCOMING_DAMAGE: i32 = 50;
         const EXPECTED_HEALTH: i32 = 61;
 
-        let mut test_character = Character::new(NAME, CLASS, SPEED, HEALTH, POWER, CRITICAL_CHANCE,
-            &attack_type, DT, DR, tags, auras, abilities);
+        let mut test_character = Character::builder("Dave").health(100).dt(1).dr(0.2).build();
         test_character.take_damage(INCOMING_DAMAGE, CRIT);
         assert_eq!(test_character.health, EXPECTED_HEALTH);
     }
 
     #[test]
     fn test_is_dead() {
-        const NAME: &str = "Dave";
-        const CLASS: &str = "Warrior";
-        const SPEED: i32 = 1;
-        const HEALTH: i32 = 10;
-        const POWER: i32 = 5;
         const CRIT: bool = false;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 0;
-        const DR: f64 = 0.0;
-        let tags: Vec<String> = vec!["null".to_string()];
-        let auras: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         const INCOMING_DAMAGE: i32 = 40;
         const EXPECTED_DEATH: bool = true;
 
-        let mut test_character = Character::new(NAME, CLASS, SPEED, HEALTH, POWER, CRITICAL_CHANCE,
-            &attack_type, DT, DR, tags, auras, abilities);
+        let mut test_character = Character::builder("Dave").health(10).build();
         test_character.take_damage(INCOMING_DAMAGE, CRIT);
         let death_result = test_character.is_dead();
 
         assert_eq!(death_result, EXPECTED_DEATH);
     }
+
+    #[test]
+    fn test_apply_formation() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        test_character.formation = vec![PositionModifier::new(Statistic::Dr, Placement::FrontRow, 0.2),
+            PositionModifier::new(Statistic::Power, Placement::BackRow, 0.1)];
+        const EXPECTED_FRONT_DR: f64 = 0.2;
+        const EXPECTED_FRONT_POWER: i32 = 50;
+        const EXPECTED_BACK_DR: f64 = 0.0;
+        const EXPECTED_BACK_POWER: i32 = 55;
+
+        test_character.apply_formation(configs::FRONT_LEFT);
+        assert_eq!(test_character.dr(), EXPECTED_FRONT_DR);
+        assert_eq!(test_character.power(), EXPECTED_FRONT_POWER);
+
+        // Moving recomputes the bonuses for the new position
+        test_character.apply_formation(configs::BACK_LEFT);
+        assert_eq!(test_character.dr(), EXPECTED_BACK_DR);
+        assert_eq!(test_character.power(), EXPECTED_BACK_POWER);
+    }
+
+    #[test]
+    fn test_clear_formation_keeps_damage() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        test_character.formation = vec![PositionModifier::new(Statistic::Health, Placement::FrontRow, 0.5)];
+        const EXPECTED_HEALTH_MAX: i32 = 100;
+        const EXPECTED_HEALTH: i32 = 90;
+
+        test_character.apply_formation(configs::FRONT_LEFT);
+        test_character.take_damage(60, false);
+        test_character.clear_formation();
+
+        assert_eq!(test_character.health_max(), EXPECTED_HEALTH_MAX);
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+    }
+
+    // Stat modification tests
+    #[test]
+    fn test_apply_modifier_aura_and_ability_agree() {
+        let mut aura_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1)
+            .dt(10).dr(0.1).build();
+        let mut ability_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1)
+            .dt(10).dr(0.1).build();
+        let statistics: Vec<Statistic> = vec![Statistic::Health, Statistic::Power, Statistic::CriticalChance,
+            Statistic::Speed, Statistic::Dt, Statistic::Dr];
+
+        for statistic in statistics.iter() {
+            aura_character.apply_modifier(*statistic, &Aura::new(*statistic, AuraTarget::Itself, 0.5), "test");
+            ability_character.apply_modifier(*statistic, &AbilityEffect::new(*statistic, AbilityTarget::Itself, 0.5), "test");
+        }
+
+        assert_eq!(aura_character, ability_character);
+    }
+
+    #[test]
+    fn test_apply_modifier_keeps_base_stats() {
+        let mut test_character = Character::builder("Dave").class("Warrior").speed(-1).power(5000)
+            .critical_chance(0.1).build();
+        const EXPECTED_BASE_POWER: i32 = 5000;
+        const EXPECTED_POWER: i32 = 6000;
+        const EXPECTED_SPEED: i32 = 1;
+
+        test_character.apply_modifier(Statistic::Power, &Aura::new(Statistic::Power, AuraTarget::Allies, 0.2), "aura: Tank");
+        test_character.apply_modifier(Statistic::Speed, &Aura::new(Statistic::Speed, AuraTarget::Allies, 2.0), "aura: Rogue");
+
+        assert_eq!(test_character.base.power, EXPECTED_BASE_POWER);
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert_eq!(test_character.speed(), EXPECTED_SPEED);
+        assert_eq!(test_character.modifiers().len(), 2);
+        assert_eq!(describe_percent_change(test_character.power() as f64, test_character.base.power as f64, ""),
+            "6000 (5000 +20%)");
+        assert_eq!(describe_flat_change(test_character.speed(), test_character.base.speed), "1 (-1 +2)");
+    }
+
+    #[test]
+    fn test_apply_modifier_modes() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(1000).critical_chance(0.1).build();
+        const EXPECTED_POWER: i32 = 1964; // (1000 + 500 + 20% of 1000) * 1.1 * 1.05 => 1963.5
+        const EXPECTED_CRIT: f64 = 0.15;
+
+        test_character.add_modifier(Modifier::new("test", Statistic::Power, ModifierMode::Flat, 500.0, None));
+        test_character.add_modifier(Modifier::new("test", Statistic::Power, ModifierMode::PercentOfBase, 0.2, None));
+        test_character.add_modifier(Modifier::new("test", Statistic::Power, ModifierMode::PercentOfCurrent, 0.1, None));
+        test_character.add_modifier(Modifier::new("test", Statistic::Power, ModifierMode::PercentOfCurrent, 0.05, None));
+        test_character.add_modifier(Modifier::new("test", Statistic::CriticalChance, ModifierMode::Flat, 0.05, None));
+
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert!((test_character.critical_chance() - EXPECTED_CRIT).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apply_modifier_set_overrides() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(1000).critical_chance(0.1).build();
+        const EXPECTED_SPEED: i32 = -5;
+
+        test_character.add_modifier(Modifier::new("test", Statistic::Speed, ModifierMode::Flat, 2.0, None));
+        test_character.add_modifier(Modifier::new("test", Statistic::Speed, ModifierMode::Set, -5.0, None));
+
+        assert_eq!(test_character.speed(), EXPECTED_SPEED);
+    }
+
+    #[test]
+    fn test_apply_modifier_repeated_buffs_add_up() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(100).critical_chance(0.1).build();
+        let ability = AbilityEffect::new(Statistic::Power, AbilityTarget::Itself, 0.1);
+        const EXPECTED_POWER: i32 = 200;
+
+        for _ in 0..10 {
+            test_character.apply_modifier(Statistic::Power, &ability, "ability: Dave");
+        }
+
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+    }
+
+    #[test]
+    fn test_apply_modifier_health_keeps_damage() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        const EXPECTED_HEALTH_MAX: i32 = 150;
+        const EXPECTED_HEALTH: i32 = 110;
+
+        test_character.take_damage(40, false);
+        test_character.apply_modifier(Statistic::Health, &Aura::new(Statistic::Health, AuraTarget::Itself, 0.5), "test");
+
+        assert_eq!(test_character.health_max(), EXPECTED_HEALTH_MAX);
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+    }
+
+    #[test]
+    fn test_add_modifier_does_not_revive() {
+        let mut test_character = Character::builder("Dave").health(100).build();
+        test_character.health = -10;
+        const EXPECTED_HEALTH: i32 = -10;
+
+        test_character.add_modifier(Modifier::new("formation", Statistic::Health, ModifierMode::PercentOfBase, 0.5, None));
+
+        assert_eq!(test_character.health, EXPECTED_HEALTH);
+        assert!(test_character.is_dead());
+    }
+
+    #[test]
+    fn test_apply_modifier_dt_and_dr() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1)
+            .dt(10).dr(0.1).build();
+        const EXPECTED_DT: i32 = 12;
+        const EXPECTED_DR: f64 = 0.3;
+
+        test_character.apply_modifier(Statistic::Dt, &Aura::new(Statistic::Dt, AuraTarget::Itself, 0.2), "test");
+        test_character.apply_modifier(Statistic::Dr, &Aura::new(Statistic::Dr, AuraTarget::Itself, 0.2), "test");
+
+        assert_eq!(test_character.dt(), EXPECTED_DT);
+        assert!((test_character.dr() - EXPECTED_DR).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_dr_capped() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1)
+            .dr(0.8).build();
+
+        test_character.apply_modifier(Statistic::Dr, &Aura::new(Statistic::Dr, AuraTarget::Itself, 0.2), "test");
+
+        assert_eq!(test_character.dr(), configs::MAXIUMUM_DAMAGE_RESIST);
+    }
+
+    #[test]
+    fn test_remove_modifiers_from() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        const EXPECTED_POWER: i32 = 55;
+
+        test_character.apply_modifier(Statistic::Power, &Aura::new(Statistic::Power, AuraTarget::Itself, 0.1), "aura: Tank");
+        test_character.apply_modifier(Statistic::Power, &Aura::new(Statistic::Power, AuraTarget::Itself, 0.5), "aura: Mage");
+        test_character.remove_modifiers_from("aura: Mage");
+
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+    }
+
+    #[test]
+    fn test_tick_modifiers() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).build();
+        let mut ability = AbilityEffect::new(Statistic::Power, AbilityTarget::Itself, 0.2);
+        ability.duration = Some(2);
+        const EXPECTED_BUFFED_POWER: i32 = 60;
+        const EXPECTED_POWER: i32 = 50;
+
+        test_character.apply_modifier(Statistic::Power, &ability, "ability: Dave");
+        test_character.tick_modifiers();
+        assert_eq!(test_character.power(), EXPECTED_BUFFED_POWER);
+
+        test_character.tick_modifiers();
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert!(test_character.modifiers().is_empty());
+    }
+
+    #[test]
+    fn test_reset_battle_counters_keeps_lasting_modifiers() {
+        let mut test_character = Character::builder("Dave").class("Warrior").power(50).build();
+        let ability = AbilityEffect::new(Statistic::Power, AbilityTarget::Itself, 0.2);
+        const EXPECTED_POWER: i32 = 55;
+
+        test_character.apply_modifier(Statistic::Power, &ability, "ability: Dave");
+        test_character.apply_modifier(Statistic::Power, &StatBonus::new(Statistic::Power, 0.1), "item: Sword");
+        test_character.kills = 2;
+        test_character.reset_battle_counters();
+
+        assert_eq!(test_character.power(), EXPECTED_POWER);
+        assert_eq!(test_character.kills, 0);
+    }
 }
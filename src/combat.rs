@@ -3,10 +3,22 @@ use crate::character;
 use character::Character;
 #[allow(unused_imports)]
 use crate::modifiers::Aura; // used by tests
-use crate::modifiers::Ability; // used by tests
-use crate::modifiers::Apply;
+use crate::modifiers::Ability;
+use crate::modifiers::AbilityTarget;
+use crate::modifiers::AbilityEffect;
+use crate::modifiers::EffectKind;
+use crate::modifiers::Movement;
+use crate::modifiers::Statistic;
+use crate::modifiers::TriggerContext;
+use crate::progression::BattleOutcome;
+use crate::squad;
+use crate::squad::Swap;
+use crate::tags::Tag;
 use crate::targeting;
+#[allow(unused_imports)]
+use crate::targeting::Guard; // used by tests
 use rand;
+use rand::Rng;
 
 // Loops through teams and pushes a vector sorted by character's speed
 pub fn calculate_initiative(hero_team: &Vec<Character>, villain_team: &Vec<Character>) -> Vec<(i32,
@@ -14,12 +26,12 @@ pub fn calculate_initiative(hero_team: &Vec<Character>, villain_team: &Vec<Chara
     let mut initiative = Vec::with_capacity(2usize * configs::TEAM_SIZE); // 2 teams
     for (index, character) in hero_team.iter().enumerate() {
         if ! character.is_dead() {
-            initiative.push((character.speed, String::from("hero"), index));
+            initiative.push((character.speed(), String::from("hero"), index));
         }
     }
     for (index, character) in villain_team.iter().enumerate() {
         if ! character.is_dead() {
-            initiative.push((character.speed, String::from("villain"), index));
+            initiative.push((character.speed(), String::from("villain"), index));
         }
     }
     // Sort by speed score; sort is ASCENDING so we can pop elements off
@@ -27,115 +39,289 @@ pub fn calculate_initiative(hero_team: &Vec<Character>, villain_team: &Vec<Chara
     return initiative
 }
 
-// Apply effects of abilities after appropriate triggers
-fn handle_abilities(trigger: &str, source: usize, allied_team: &mut Vec<Character>,
-    enemy_team: &mut Vec<Character>) {
+// Apply effects of abilities after appropriate triggers; returns the position swaps made on the
+// allied and enemy teams (in that order) so the initiative order can follow the characters.
+// Opponents are the enemies involved in the event: the characters hit on "attack", or the attacker
+// on "attacked".
+fn handle_abilities(trigger: &str, source: usize, opponents: &[bool], allied_team: &mut Vec<Character>,
+    enemy_team: &mut Vec<Character>) -> (Vec<Swap>, Vec<Swap>) {
     /*
     Stages of this function:
         - Stage 1: collect the relevant abilities to be applied
         - Stage 2: route and apply based on the appropriate event trigger
+        - Stage 3: move characters (after stat changes, since moving changes positions)
 
-    Only handles on attack effects right now
+    Only handles on attack, attacked, and battle start effects right now
     */
+    let mut allied_swaps: Vec<Swap> = Vec::new();
+    let mut enemy_swaps: Vec<Swap> = Vec::new();
     // Stage 1
-    let source_ability_list: Vec<Ability> = allied_team[source].abilities.clone();
-    let mut relevant_source_abilities: Vec<Ability> = Vec::new();
-    for ability in source_ability_list.iter() {
-        if ability.check_ability_trigger(trigger) {
-            relevant_source_abilities.push(ability.clone());
+    let context: TriggerContext = trigger_context(source, opponents, allied_team, enemy_team);
+    let mut triggered_abilities: Vec<Ability> = Vec::new();
+    for ability in allied_team[source].abilities.iter_mut() {
+        if ability.check_ability_trigger(trigger, &context, rand::random::<f64>()) {
+            ability.activations += 1;
+            triggered_abilities.push(ability.clone());
         }
     }
+    for ability in triggered_abilities.iter().filter(|ability| !ability.name.is_empty()) {
+        allied_team[source].print_used_ability(&ability.name);
+    }
+    let effects: Vec<AbilityEffect> = triggered_abilities.into_iter().flat_map(|ability| ability.effects).collect();
     // Stage 2
-    if trigger == "attack" {
-        for ability in relevant_source_abilities.iter() {
-            // Allies
-            if ability.target == "allies".to_string() {
-                for character_current in allied_team.iter_mut() {
-                    if ability.statistic == "health".to_string() {
-                        let new_health: i32 = ability.change_health(character_current.health, character_current.health_max);
-                        character_current.health_max = new_health;
-                        character_current.health = new_health;
-                    } else if ability.statistic == "power".to_string() {
-                        let new_power: i32 = ability.change_power(character_current.power);
-                        character_current.power = new_power;
-                    } else if ability.statistic == "critical chance".to_string() {
-                        let new_crit: f64 = ability.change_crit_chance(character_current.critical_chance);
-                        character_current.critical_chance = new_crit;
-                    } else if ability.statistic == "speed".to_string() {
-                        let new_speed: i32 = ability.change_speed(character_current.speed);
-                        character_current.speed = new_speed;
-                    }
+    if trigger == "attack" || trigger == "attacked" || trigger == "battle_start" {
+        for effect in effects.iter().filter(|effect| effect.movement.is_none()) {
+            let modifier_source: String = format!("{}{}", character::ABILITY_SOURCE_PREFIX, allied_team[source].name);
+            let source_power: i32 = allied_team[source].power();
+            let (allied_recipients, enemy_recipients) = ability_recipients(effect.target, trigger, source,
+                opponents, allied_team, enemy_team);
+            for position in allied_recipients {
+                apply_effect(effect, &mut allied_team[position], source_power, &modifier_source);
+            }
+            for position in enemy_recipients {
+                if apply_effect(effect, &mut enemy_team[position], source_power, &modifier_source) {
+                    allied_team[source].kills += 1;
                 }
-            } else if ability.target == "enemies".to_string() {
-                // Enemies
-                for character_current in enemy_team.iter_mut () {
-                    if ability.statistic == "health".to_string() {
-                        let new_health: i32 = ability.change_health(character_current.health, character_current.health_max);
-                        character_current.health_max = new_health;
-                        character_current.health = new_health;
-                    } else if ability.statistic == "power".to_string() {
-                        let new_power: i32 = ability.change_power(character_current.power);
-                        character_current.power = new_power;
-                    } else if ability.statistic == "critical chance".to_string() {
-                        let new_crit: f64 = ability.change_crit_chance(character_current.critical_chance);
-                        character_current.critical_chance = new_crit;
-                    } else if ability.statistic == "speed".to_string() {
-                        let new_speed: i32 = ability.change_speed(character_current.speed);
-                        character_current.speed = new_speed;
+            }
+        }
+    } else if trigger == "killed" {
+
+    } else if trigger == "died" {
+
+    }
+    // Stage 3
+    for effect in effects.iter() {
+        match effect.movement {
+            Some(Movement::Push) => {
+                let (_, pushed) = ability_recipients(effect.target, trigger, source, opponents, allied_team,
+                    enemy_team);
+                for position in pushed {
+                    if let Some(swap) = squad::push_to_front(enemy_team, position) {
+                        enemy_swaps.push(swap);
                     }
                 }
-            } else if ability.target == "self".to_string() {
-                if ability.statistic == "health".to_string() {
-                    let new_health: i32 = ability.change_health(allied_team[source].health, allied_team[source].health_max);
-                    allied_team[source].health_max = new_health;
-                    allied_team[source].health = new_health;
-                } else if ability.statistic == "power".to_string() {
-                    let new_power: i32 = ability.change_power(allied_team[source].power);
-                    allied_team[source].power = new_power;
-                } else if ability.statistic == "critical chance".to_string() {
-                    let new_crit: f64 = ability.change_crit_chance(allied_team[source].critical_chance);
-                    allied_team[source].critical_chance = new_crit;
-                } else if ability.statistic == "speed".to_string() {
-                    let new_speed: i32 = ability.change_speed(allied_team[source].speed);
-                    allied_team[source].speed = new_speed;
+            },
+            Some(Movement::Retreat) => {
+                if let Some(swap) = squad::retreat_to_back(allied_team, source) {
+                    allied_swaps.push(swap);
                 }
+            },
+            None => {}
+        }
+    }
+    (allied_swaps, enemy_swaps)
+}
+
+// Apply one ability effect to a recipient (source power scales damage effects); returns whether
+// the effect killed the recipient
+fn apply_effect(effect: &AbilityEffect, recipient: &mut Character, source_power: i32, modifier_source: &str) -> bool {
+    match effect.kind {
+        EffectKind::Modifier => {
+            if let Some(statistic) = effect.statistic {
+                recipient.apply_modifier(statistic, effect, modifier_source);
+            }
+        },
+        EffectKind::MaxHealth => recipient.apply_modifier(Statistic::Health, effect, modifier_source),
+        EffectKind::Heal => {
+            let amount: i32 = effect.amount(recipient.health_max());
+            recipient.heal(amount);
+        },
+        EffectKind::Damage => {
+            if recipient.is_dead() {
+                return false;
+            }
+            let amount: i32 = effect.amount(source_power);
+            if effect.bypass_armor {
+                recipient.take_direct_damage(amount);
+            } else {
+                recipient.take_damage(amount, false);
+            }
+            if recipient.is_dead() {
+                recipient.print_died();
+                return true;
+            }
+        }
+    }
+    false
+}
+
+// Positions on the allied and enemy teams (in that order) that receive an ability's effect. The
+// attacked and attacker targets only reach anyone on their own trigger (see AbilityTarget::trigger).
+fn ability_recipients(target: AbilityTarget, trigger: &str, source: usize, opponents: &[bool],
+    allied_team: &[Character], enemy_team: &[Character]) -> (Vec<usize>, Vec<usize>) {
+    match target {
+        AbilityTarget::Itself => (vec![source], Vec::new()),
+        AbilityTarget::Allies => ((0..allied_team.len()).collect(), Vec::new()),
+        AbilityTarget::Enemies => (Vec::new(), (0..enemy_team.len()).collect()),
+        AbilityTarget::Attacked | AbilityTarget::Attacker => {
+            if target.trigger() != Some(trigger) {
+                return (Vec::new(), Vec::new());
             }
+            let marked: Vec<usize> = opponents.iter().enumerate()
+                .filter(|(position, involved)| **involved && *position < enemy_team.len())
+                .map(|(position, _)| position)
+                .collect();
+            (Vec::new(), marked)
+        },
+        AbilityTarget::LowestHealthAlly => (lowest_health_position(allied_team).into_iter().collect(), Vec::new()),
+        AbilityTarget::RandomEnemy => (Vec::new(), random_living_position(enemy_team).into_iter().collect())
+    }
+}
+
+// Living character with the smallest fraction of their maximum health (earliest position on ties)
+fn lowest_health_position(team: &[Character]) -> Option<usize> {
+    let mut lowest: Option<(usize, f64)> = None;
+    for (position, character) in team.iter().enumerate() {
+        if character.is_dead() {
+            continue;
         }
-    } else if trigger == "attacked" {
+        let health_fraction: f64 = character.health as f64 / character.health_max() as f64;
+        match lowest {
+            Some((_, lowest_fraction)) if lowest_fraction <= health_fraction => {},
+            _ => lowest = Some((position, health_fraction))
+        }
+    }
+    lowest.map(|(position, _)| position)
+}
 
-    } else if trigger == "killed" {
+fn random_living_position(team: &[Character]) -> Option<usize> {
+    let living: Vec<usize> = (0..team.len()).filter(|position| !team[*position].is_dead()).collect();
+    if living.is_empty() {
+        return None;
+    }
+    Some(living[rand::thread_rng().gen_range(0, living.len())])
+}
 
-    } else if trigger == "died" {
+// Follow a position through a series of swaps
+fn follow_swaps(mut position: usize, swaps: &[Swap]) -> usize {
+    for (position_a, position_b) in swaps.iter() {
+        if position == *position_a {
+            position = *position_b;
+        } else if position == *position_b {
+            position = *position_a;
+        }
+    }
+    position
+}
 
+// Snapshot of the battle used to check ability conditions
+fn trigger_context(source: usize, opponents: &[bool], allied_team: &[Character], enemy_team: &[Character]) -> TriggerContext {
+    let character: &Character = &allied_team[source];
+    let target_tags: Vec<Tag> = opponents.iter().zip(enemy_team.iter())
+        .filter(|(was_attacked, _)| **was_attacked)
+        .flat_map(|(_, target)| target.tags.iter().copied())
+        .collect();
+    TriggerContext {
+        health_fraction: character.health as f64 / character.health_max() as f64,
+        target_tags,
+        allies_alive: allied_team.iter().filter(|ally| !ally.is_dead()).count()
     }
+}
 
+// Keep the remaining initiative entries pointing at the same characters after they trade places
+fn swap_initiative(initiative_order: &mut [(i32, String, usize)], team: &str, swaps: &[Swap]) {
+    for (position_a, position_b) in swaps.iter() {
+        for entry in initiative_order.iter_mut() {
+            if entry.1 == team {
+                if entry.2 == *position_a {
+                    entry.2 = *position_b;
+                } else if entry.2 == *position_b {
+                    entry.2 = *position_a;
+                }
+            }
+        }
+    }
+}
+
+// A single character's turn: attack, trigger abilities, and let the enemy back row step up if needed.
+// Returns the position swaps made on the allied and enemy teams.
+fn take_turn(index: usize, allied_team: &mut Vec<Character>, enemy_team: &mut Vec<Character>,
+    enemy_team_alive: Vec<bool>) -> (Vec<Swap>, Vec<Swap>) {
+    let attacked: Vec<bool> = attack(&mut allied_team[index], enemy_team, enemy_team_alive);
+    let (mut allied_swaps, mut enemy_swaps) = handle_abilities("attack", index, &attacked, allied_team, enemy_team);
+    // Surviving targets react to the attack. Reactions come after any movement, so the attacker and
+    // targets are followed through each swap made so far.
+    let mut attacker: usize = follow_swaps(index, &allied_swaps);
+    let mut targets: Vec<usize> = attacked.iter().enumerate()
+        .filter(|(_, was_attacked)| **was_attacked)
+        .map(|(position, _)| follow_swaps(position, &enemy_swaps))
+        .collect();
+    for i in 0..targets.len() {
+        let target: usize = targets[i];
+        if enemy_team[target].is_dead() || allied_team[attacker].is_dead() {
+            continue;
+        }
+        let mut attacker_mark: Vec<bool> = vec![false; configs::TEAM_SIZE];
+        attacker_mark[attacker] = true;
+        let (reactive_swaps, attacker_swaps) = handle_abilities("attacked", target, &attacker_mark, enemy_team,
+            allied_team);
+        attacker = follow_swaps(attacker, &attacker_swaps);
+        for remaining in targets[i + 1..].iter_mut() {
+            *remaining = follow_swaps(*remaining, &reactive_swaps);
+        }
+        enemy_swaps.extend(reactive_swaps);
+        allied_swaps.extend(attacker_swaps);
+    }
+    if configs::BACK_ROW_ADVANCES {
+        enemy_swaps.extend(squad::advance_back_row(enemy_team));
+    }
+    (allied_swaps, enemy_swaps)
+}
+
+// Look for a living guardian protecting the target (each eligible guardian rolls in squad order)
+fn find_guardian(enemy_team: &[Character], target: usize) -> Option<usize> {
+    for (index, character) in enemy_team.iter().enumerate() {
+        if let Some(guard) = &character.guard {
+            if !character.is_dead() && guard.covers(index, target) && rand::random::<f64>() < guard.chance {
+                return Some(index);
+            }
+        }
+    }
+    None
 }
 
 // Call out to Targeting module to decide targets, then attacks targets (per character)
 fn attack(attacker: &mut Character, enemy_team: &mut Vec<Character>, enemy_team_alive: Vec<bool>) -> Vec<bool> {
     let attack_type: &str = &attacker.attack_type;
-    let targeting_data: Vec<bool> = targeting::attack_type_to_coordinates(attack_type,
-        enemy_team_alive, &attacker.tags);
+    let enemy_tags: Vec<Vec<Tag>> = enemy_team.iter().map(|character| character.tags.clone()).collect();
+    let decision: targeting::TargetingDecision = targeting::explain_targets(attack_type, enemy_team_alive,
+        &attacker.tags, &enemy_tags);
+    if configs::DEBUG_TARGETING {
+        println!("[targeting] {}: {}", attacker.name, decision.describe());
+    }
+    let targeting_data: Vec<bool> = decision.targets;
+    let single_target: bool = targeting::is_single_target(attack_type);
     let mut attacked_targets: Vec<bool> = vec![false; configs::TEAM_SIZE];
     for i in 0..enemy_team.len() {
         if targeting_data[i] { // If attacker should attack target
+            // Guardians can intercept single-target attacks and take the hit themselves
+            let mut target: usize = i;
+            if single_target {
+                if let Some(guardian) = find_guardian(enemy_team, i) {
+                    target = guardian;
+                }
+            }
             // Check for crit on attack
             let roll_to_crit: f64 = rand::random::<f64>();
             let mut crit: bool = false;
-            if roll_to_crit < attacker.critical_chance {
+            if roll_to_crit < attacker.critical_chance() {
                 crit = true;
             }
             // Make attack(s)
             attacker.print_attacking(&enemy_team[i].name);
+            if target != i {
+                enemy_team[target].print_intercepting(&enemy_team[i].name);
+            }
             if crit {
-                enemy_team[i].take_damage(attacker.power * configs::CRITICAL_MULTIPLIER, crit);
+                enemy_team[target].take_damage(attacker.power() * configs::CRITICAL_MULTIPLIER, crit);
             } else {
-                enemy_team[i].take_damage(attacker.power, crit);
+                enemy_team[target].take_damage(attacker.power(), crit);
             }
-            if enemy_team[i].is_dead() {
-                enemy_team[i].print_died();
+            if enemy_team[target].is_dead() {
+                enemy_team[target].print_died();
+                attacker.kills += 1;
             } else {
-                attacked_targets[i] = true; // if alive, mark as attacked
+                attacked_targets[target] = true; // if alive, mark as attacked
             }
         }
     }
@@ -144,9 +330,9 @@ fn attack(attacker: &mut Character, enemy_team: &mut Vec<Character>, enemy_team_
 
 
 
-// Loop through in initiative order and attack, then check exit conditions
+// Loop through in initiative order and attack
 fn run_combat_round(mut initiative_order: Vec<(i32, String, usize)>,
-hero_team: &mut Vec<Character>, villain_team: &mut Vec<Character>) -> bool {
+hero_team: &mut Vec<Character>, villain_team: &mut Vec<Character>) {
     for _i in 0..initiative_order.len() {
         let initiative_metadata = initiative_order.pop().unwrap();
         let hero_index: usize = initiative_metadata.2;
@@ -173,86 +359,381 @@ hero_team: &mut Vec<Character>, villain_team: &mut Vec<Character>) -> bool {
         // Route the attacks properly: heroes attack villains, villains attack heros
         if team_assignment == String::from("hero") {
             if ! hero_team[hero_index].is_dead() {
-                let _alive_targets: Vec<bool> = attack(&mut hero_team[hero_index], villain_team, villain_team_alive);
-                handle_abilities("attack", hero_index, hero_team, villain_team);
+                let (hero_swaps, villain_swaps) = take_turn(hero_index, hero_team, villain_team, villain_team_alive);
+                swap_initiative(&mut initiative_order, "hero", &hero_swaps);
+                swap_initiative(&mut initiative_order, "villain", &villain_swaps);
             }
         } else {
             if ! villain_team[hero_index].is_dead() {
-                let _alive_targets: Vec<bool> = attack(&mut villain_team[hero_index], hero_team, hero_team_alive);
-                handle_abilities("attack", hero_index, villain_team, hero_team);
+                let (villain_swaps, hero_swaps) = take_turn(hero_index, villain_team, hero_team, hero_team_alive);
+                swap_initiative(&mut initiative_order, "hero", &hero_swaps);
+                swap_initiative(&mut initiative_order, "villain", &villain_swaps);
             }
         }
     }
-    // Check to see if either team has been completely wiped out
-    let mut heroes_alive: bool = false;
-    'outer1: for character in hero_team.iter_mut() {
-        if ! character.is_dead() {
-            heroes_alive = true;
-            break 'outer1;
-        }
-    }
-    let mut villains_alive: bool = false;
-    'outer2: for character in villain_team.iter_mut() {
-        if ! character.is_dead() {
-            villains_alive = true;
-            break 'outer2;
-        }
+}
+
+// A team can keep fighting while anyone is standing or someone is waiting on the bench
+fn team_standing(team: &[Character], reserves: &[Character]) -> bool {
+    team.iter().any(|character| !character.is_dead()) || !reserves.is_empty()
+}
+
+// Trigger battle start abilities for the characters at the given positions
+fn trigger_battle_start(positions: &[usize], allied_team: &mut Vec<Character>, enemy_team: &mut Vec<Character>) {
+    for position in positions.iter() {
+        handle_abilities("battle_start", *position, &[], allied_team, enemy_team);
     }
-    let continue_combat: bool = heroes_alive && villains_alive;
-    return continue_combat;
 }
 
-pub fn run_combat(hero_team: &mut Vec<Character>, villain_team: &mut Vec<Character>) {
+pub fn run_combat(hero_team: &mut Vec<Character>, villain_team: &mut Vec<Character>) -> BattleOutcome {
+    run_combat_with_reserves(hero_team, &mut Vec::new(), villain_team, &mut Vec::new()).0
+}
+
+// Fallen characters are replaced by the next reserve at the start of the following round, so the
+// teams keep their squad positions. Returns how the battle went for the heroes, along with the
+// heroes replaced by reserves (the outcome covers them after the team, so the experience and
+// injuries that follow from it reach everyone who fought).
+pub fn run_combat_with_reserves(hero_team: &mut Vec<Character>, hero_reserves: &mut Vec<Character>,
+    villain_team: &mut Vec<Character>, villain_reserves: &mut Vec<Character>) -> (BattleOutcome, Vec<Character>) {
+    for character in hero_team.iter_mut().chain(hero_reserves.iter_mut())
+        .chain(villain_team.iter_mut()).chain(villain_reserves.iter_mut()) {
+        character.reset_battle_counters();
+    }
+    let hero_positions: Vec<usize> = (0..hero_team.len()).collect();
+    let villain_positions: Vec<usize> = (0..villain_team.len()).collect();
+    trigger_battle_start(&hero_positions, hero_team, villain_team);
+    trigger_battle_start(&villain_positions, villain_team, hero_team);
+    let mut hero_fallen: Vec<Character> = Vec::new();
+    let mut villain_fallen: Vec<Character> = Vec::new();
     let mut loop_count: u32 = 1;
     loop {
         println!("\n# --- ROUND {} --- #", loop_count);
+        if loop_count > 1 {
+            let hero_entrants: Vec<usize> = squad::substitute_reserves(hero_team, hero_reserves, villain_team,
+                &mut hero_fallen);
+            trigger_battle_start(&hero_entrants, hero_team, villain_team);
+            let villain_entrants: Vec<usize> = squad::substitute_reserves(villain_team, villain_reserves,
+                hero_team, &mut villain_fallen);
+            trigger_battle_start(&villain_entrants, villain_team, hero_team);
+        }
         let init = calculate_initiative(&hero_team, &villain_team);
-        let continue_combat: bool = run_combat_round(init, hero_team, villain_team);
+        run_combat_round(init, hero_team, villain_team);
+        // Temporary modifiers wear off at the end of the round
+        for character in hero_team.iter_mut().chain(villain_team.iter_mut()) {
+            character.tick_modifiers();
+        }
+        let continue_combat: bool = team_standing(hero_team, hero_reserves)
+            && team_standing(villain_team, villain_reserves);
         if ! continue_combat {
             break;
         }
         loop_count += 1;
     }
+    let outcome: BattleOutcome = BattleOutcome::from_teams(hero_team, &hero_fallen, villain_team, &villain_fallen,
+        villain_reserves);
+    (outcome, hero_fallen)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::targeting::Ward;
+    use crate::test_helpers::test_squad;
 
     // Character Struct tests
     #[test]
     fn test_calculate_initiave() {
-        const WILLIAM: &str = "William";
-        const LOGAN: &str = "Logan";
-        const CLASS: &str = "Warrior";
-        const SPEED_WILLIAM: i32 = 1;
-        const SPEED_LOGAN: i32 = 0;
-        const HEALTH: i32 = 10;
-        const POWER: i32 = 5;
-        const CRITICAL_CHANCE: f64 = 0.0;
-        let attack_type: String = "single".to_string();
-        const DT: i32 = 1;
-        const DR: f64 = 0.0;
-        let tags_b: Vec<String> = vec!["null".to_string()];
-        let tags_l: Vec<String> = vec!["null".to_string()];
-        let auras_b: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities_b: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
-        let auras_l: Vec<Aura> = vec![Aura::new("null", "null", 0.0)];
-        let abilities_l: Vec<Ability> = vec![Ability::new("null", "null", 0.0, "null")];
         let expected_init: Vec<(i32, String, usize)> = vec![(0, "villain".to_string(), 0usize),
             (1, "hero".to_string(), 0usize)];
 
-        // While mutability is unused in test, all of these must be mutable to satisfy fn conditions
-        #[allow(unused_mut)]
-        let mut bill = Character::new(WILLIAM, CLASS, SPEED_WILLIAM, HEALTH, POWER, CRITICAL_CHANCE, &attack_type, DT, DR, tags_b, auras_b, abilities_b);
-        #[allow(unused_mut)]
-        let mut logan = Character::new(LOGAN, CLASS, SPEED_LOGAN, HEALTH, POWER, CRITICAL_CHANCE, &attack_type, DT, DR, tags_l, auras_l, abilities_l);
-        #[allow(unused_mut)]
-        let mut heroes: Vec<Character> = vec![bill];
-        #[allow(unused_mut)]
-        let mut villains: Vec<Character> = vec![logan];
+        let heroes: Vec<Character> = vec![Character::builder("William").speed(1).build()];
+        let villains: Vec<Character> = vec![Character::builder("Logan").speed(0).build()];
         let init = calculate_initiative(&heroes, &villains);
 
         assert_eq!(init, expected_init);
     }
+
+    // Guardian tests
+    fn guardian_test_squad(guard_chance: f64) -> Vec<Character> {
+        let mut squad: Vec<Character> = test_squad();
+        squad[configs::FRONT_LEFT].guard = Some(Guard::new(Ward::BackRow, guard_chance));
+        squad[configs::FRONT_LEFT].base.dr = 0.5;
+        squad
+    }
+
+    #[test]
+    fn test_attack_guardian_intercepts() {
+        let mut attacker = Character::builder("Rogue").class("Rogue").speed(0).power(40).tags(vec![Tag::Flanker]).build();
+        let mut defenders: Vec<Character> = guardian_test_squad(1.0);
+        const EXPECTED_GUARDIAN_HEALTH: i32 = 80; // guardian's own dr applies
+        const EXPECTED_WARD_HEALTH: i32 = 100;
+
+        let attacked = attack(&mut attacker, &mut defenders, vec![true; 4]);
+
+        assert_eq!(defenders[configs::FRONT_LEFT].health, EXPECTED_GUARDIAN_HEALTH);
+        assert_eq!(defenders[configs::BACK_RIGHT].health, EXPECTED_WARD_HEALTH);
+        assert!(attacked[configs::FRONT_LEFT]);
+        assert!(!attacked[configs::BACK_RIGHT]);
+    }
+
+    #[test]
+    fn test_attack_guardian_fails_to_intercept() {
+        let mut attacker = Character::builder("Rogue").class("Rogue").speed(0).power(40).tags(vec![Tag::Flanker]).build();
+        let mut defenders: Vec<Character> = guardian_test_squad(0.0);
+        const EXPECTED_GUARDIAN_HEALTH: i32 = 100;
+        const EXPECTED_WARD_HEALTH: i32 = 60;
+
+        attack(&mut attacker, &mut defenders, vec![true; 4]);
+
+        assert_eq!(defenders[configs::FRONT_LEFT].health, EXPECTED_GUARDIAN_HEALTH);
+        assert_eq!(defenders[configs::BACK_RIGHT].health, EXPECTED_WARD_HEALTH);
+    }
+
+    // Repositioning tests
+    #[test]
+    fn test_swap_initiative() {
+        let mut initiative: Vec<(i32, String, usize)> = vec![(0, "hero".to_string(), 0usize),
+            (1, "villain".to_string(), 2usize), (2, "hero".to_string(), 2usize)];
+        let swaps: Vec<Swap> = vec![(configs::FRONT_LEFT, configs::BACK_LEFT)];
+        let expected_initiative: Vec<(i32, String, usize)> = vec![(0, "hero".to_string(), 2usize),
+            (1, "villain".to_string(), 2usize), (2, "hero".to_string(), 0usize)];
+
+        swap_initiative(&mut initiative, "hero", &swaps);
+
+        assert_eq!(initiative, expected_initiative);
+    }
+
+    #[test]
+    fn test_handle_abilities_push_and_retreat() {
+        let mut attacker_team: Vec<Character> = test_squad();
+        let mut defender_team: Vec<Character> = test_squad();
+        attacker_team[configs::FRONT_RIGHT].abilities = vec![Ability::new_movement(Movement::Push, AbilityTarget::Attacked, "attack"),
+            Ability::new_movement(Movement::Retreat, AbilityTarget::Itself, "attack")];
+        let attacked: Vec<bool> = vec![false, false, false, true];
+
+        let (allied_swaps, enemy_swaps) = handle_abilities("attack", configs::FRONT_RIGHT, &attacked,
+            &mut attacker_team, &mut defender_team);
+
+        assert_eq!(allied_swaps, vec![(configs::FRONT_RIGHT, configs::BACK_RIGHT)]);
+        assert_eq!(enemy_swaps, vec![(configs::BACK_RIGHT, configs::FRONT_RIGHT)]);
+        assert_eq!(attacker_team[configs::BACK_RIGHT].name, "Fighter");
+        assert_eq!(defender_team[configs::FRONT_RIGHT].name, "Archer");
+    }
+
+    #[test]
+    fn test_handle_abilities_max_activations() {
+        let mut attacker_team: Vec<Character> = test_squad();
+        let mut defender_team: Vec<Character> = test_squad();
+        let mut ability = Ability::new(Statistic::Speed, AbilityTarget::Itself, 1.0, "attack");
+        ability.max_activations = Some(1);
+        attacker_team[configs::FRONT_LEFT].abilities = vec![ability];
+        let attacked: Vec<bool> = vec![true, false, false, false];
+        const EXPECTED_SPEED: i32 = 1;
+
+        for _ in 0..2 {
+            handle_abilities("attack", configs::FRONT_LEFT, &attacked, &mut attacker_team, &mut defender_team);
+        }
+
+        assert_eq!(attacker_team[configs::FRONT_LEFT].speed(), EXPECTED_SPEED);
+        assert_eq!(attacker_team[configs::FRONT_LEFT].abilities[0].activations, 1);
+    }
+
+    #[test]
+    fn test_handle_abilities_attacked_target() {
+        let mut attacker_team: Vec<Character> = test_squad();
+        let mut defender_team: Vec<Character> = test_squad();
+        attacker_team[configs::FRONT_LEFT].abilities = vec![Ability::new(Statistic::Speed, AbilityTarget::Attacked, -1.0, "attack")];
+        let attacked: Vec<bool> = vec![false, true, false, false];
+        let expected_speeds: Vec<i32> = vec![0, -1, 0, 0];
+
+        handle_abilities("attack", configs::FRONT_LEFT, &attacked, &mut attacker_team, &mut defender_team);
+        let speeds: Vec<i32> = defender_team.iter().map(|character| character.speed()).collect();
+
+        assert_eq!(speeds, expected_speeds);
+    }
+
+    #[test]
+    fn test_handle_abilities_attacker_target_on_attack() {
+        let mut attacker_team: Vec<Character> = test_squad();
+        let mut defender_team: Vec<Character> = test_squad();
+        attacker_team[configs::FRONT_LEFT].abilities = vec![Ability::new(Statistic::Speed, AbilityTarget::Attacker, -1.0, "attack")];
+        let attacked: Vec<bool> = vec![false, true, false, false];
+
+        handle_abilities("attack", configs::FRONT_LEFT, &attacked, &mut attacker_team, &mut defender_team);
+
+        assert!(defender_team.iter().all(|character| character.speed() == 0)); // the attacker isn't an enemy here
+    }
+
+    #[test]
+    fn test_take_turn_attacker_target() {
+        let mut attacker_team: Vec<Character> = test_squad();
+        let mut defender_team: Vec<Character> = test_squad();
+        defender_team[configs::FRONT_LEFT].abilities = vec![Ability::new(Statistic::Speed, AbilityTarget::Attacker, -1.0, "attacked")];
+        let alive: Vec<bool> = vec![true; 4];
+        const EXPECTED_SPEED: i32 = -1;
+
+        take_turn(configs::BACK_RIGHT, &mut attacker_team, &mut defender_team, alive);
+
+        assert_eq!(attacker_team[configs::BACK_RIGHT].speed(), EXPECTED_SPEED);
+        assert_eq!(attacker_team[configs::FRONT_LEFT].speed(), 0);
+    }
+
+    #[test]
+    fn test_handle_abilities_heal_keeps_max_health() {
+        let mut allied_team: Vec<Character> = test_squad();
+        let mut enemy_team: Vec<Character> = test_squad();
+        allied_team[configs::FRONT_LEFT].abilities = vec![Ability::new_kind(EffectKind::Heal, AbilityTarget::Allies, 0.25, "attack")];
+        allied_team[configs::FRONT_RIGHT].health = 50;
+        allied_team[configs::BACK_LEFT].health = 90;
+        let expected_health: Vec<i32> = vec![100, 75, 100, 100];
+        const EXPECTED_HEALTH_MAX: i32 = 100;
+
+        handle_abilities("attack", configs::FRONT_LEFT, &[], &mut allied_team, &mut enemy_team);
+        let health: Vec<i32> = allied_team.iter().map(|character| character.health).collect();
+
+        assert_eq!(health, expected_health);
+        assert!(allied_team.iter().all(|character| character.health_max() == EXPECTED_HEALTH_MAX));
+    }
+
+    #[test]
+    fn test_handle_abilities_max_health() {
+        let mut allied_team: Vec<Character> = test_squad();
+        let mut enemy_team: Vec<Character> = test_squad();
+        allied_team[configs::FRONT_LEFT].abilities = vec![Ability::new_kind(EffectKind::MaxHealth, AbilityTarget::Itself, -0.2, "attack")];
+        allied_team[configs::FRONT_LEFT].health = 50;
+        const EXPECTED_HEALTH: i32 = 30;
+        const EXPECTED_HEALTH_MAX: i32 = 80;
+
+        handle_abilities("attack", configs::FRONT_LEFT, &[], &mut allied_team, &mut enemy_team);
+
+        assert_eq!(allied_team[configs::FRONT_LEFT].health, EXPECTED_HEALTH);
+        assert_eq!(allied_team[configs::FRONT_LEFT].health_max(), EXPECTED_HEALTH_MAX);
+    }
+
+    #[test]
+    fn test_handle_abilities_damage() {
+        let mut allied_team: Vec<Character> = test_squad();
+        let mut enemy_team: Vec<Character> = test_squad();
+        let armored = AbilityEffect::new_kind(EffectKind::Damage, AbilityTarget::Attacked, 0.8);
+        let mut piercing = armored.clone();
+        piercing.bypass_armor = true;
+        allied_team[configs::FRONT_LEFT].abilities = vec![Ability::new_composite("Double Strike", "", "attack",
+            vec![armored, piercing])];
+        enemy_team[configs::FRONT_LEFT].base.dr = 0.5;
+        let attacked: Vec<bool> = vec![true, false, false, false];
+        const EXPECTED_HEALTH: i32 = 40; // 40 damage halved by the tank's 50% dr, then 40 more
+
+        handle_abilities("attack", configs::FRONT_LEFT, &attacked, &mut allied_team, &mut enemy_team);
+
+        assert_eq!(enemy_team[configs::FRONT_LEFT].health, EXPECTED_HEALTH);
+    }
+
+    #[test]
+    fn test_lowest_health_position() {
+        let mut team: Vec<Character> = test_squad();
+        team[configs::FRONT_LEFT].health = 0;
+        team[configs::FRONT_RIGHT].health = 60;
+        team[configs::BACK_RIGHT].health = 40;
+
+        assert_eq!(lowest_health_position(&team), Some(configs::BACK_RIGHT));
+    }
+
+    #[test]
+    fn test_random_living_position() {
+        let mut team: Vec<Character> = test_squad();
+        for position in [configs::FRONT_LEFT, configs::FRONT_RIGHT, configs::BACK_RIGHT].iter() {
+            team[*position].health = 0;
+        }
+
+        assert_eq!(random_living_position(&team), Some(configs::BACK_LEFT));
+        team[configs::BACK_LEFT].health = 0;
+        assert_eq!(random_living_position(&team), None);
+    }
+
+    #[test]
+    fn test_follow_swaps() {
+        let swaps: Vec<Swap> = vec![(configs::FRONT_LEFT, configs::BACK_LEFT), (configs::BACK_LEFT, configs::BACK_RIGHT)];
+
+        assert_eq!(follow_swaps(configs::FRONT_LEFT, &swaps), configs::BACK_RIGHT);
+        assert_eq!(follow_swaps(configs::FRONT_RIGHT, &swaps), configs::FRONT_RIGHT);
+    }
+
+    #[test]
+    fn test_trigger_context() {
+        let mut allied_team: Vec<Character> = test_squad();
+        let mut enemy_team: Vec<Character> = test_squad();
+        allied_team[configs::FRONT_LEFT].health = 25;
+        allied_team[configs::BACK_RIGHT].health = 0;
+        enemy_team[configs::FRONT_RIGHT].tags = vec![Tag::Undead];
+        let attacked: Vec<bool> = vec![false, true, false, false];
+        let expected_context = TriggerContext {health_fraction: 0.25, target_tags: vec![Tag::Undead],
+            allies_alive: 3};
+
+        assert_eq!(trigger_context(configs::FRONT_LEFT, &attacked, &allied_team, &enemy_team), expected_context);
+    }
+
+    // Reserve tests
+    #[test]
+    fn test_team_standing() {
+        let mut team: Vec<Character> = test_squad();
+        for character in team.iter_mut() {
+            character.health = 0;
+        }
+        let reserves: Vec<Character> = test_squad();
+
+        assert!(!team_standing(&team, &[]));
+        assert!(team_standing(&team, &reserves));
+    }
+
+    #[test]
+    fn test_trigger_battle_start() {
+        let mut heroes: Vec<Character> = test_squad();
+        let mut villains: Vec<Character> = test_squad();
+        heroes[configs::BACK_LEFT].abilities = vec![Ability::new(Statistic::Speed, AbilityTarget::Allies, 1.0, "battle_start")];
+        const EXPECTED_HERO_SPEED: i32 = 1;
+        const EXPECTED_VILLAIN_SPEED: i32 = 0;
+
+        trigger_battle_start(&[configs::BACK_LEFT], &mut heroes, &mut villains);
+
+        assert_eq!(heroes[configs::FRONT_LEFT].speed(), EXPECTED_HERO_SPEED);
+        assert_eq!(villains[configs::FRONT_LEFT].speed(), EXPECTED_VILLAIN_SPEED);
+    }
+    #[test]
+    fn test_run_combat_keeps_replaced_characters() {
+        let mut heroes: Vec<Character> = (0..4).map(|_| Character::builder("Glass").health(1).power(0).build()).collect();
+        let mut hero_reserves: Vec<Character> = vec![Character::builder("Champion").health(10000).power(1000)
+            .attack_type("all").build()];
+        let mut villains: Vec<Character> = (0..4).map(|_| Character::builder("Brute").health(50).power(10)
+            .attack_type("all").build()).collect();
+        const EXPECTED_TEAM_SIZE: usize = 4;
+
+        let (outcome, fallen) = run_combat_with_reserves(&mut heroes, &mut hero_reserves, &mut villains,
+            &mut Vec::new());
+
+        assert!(outcome.victory);
+        assert_eq!(heroes.len(), EXPECTED_TEAM_SIZE);
+        assert_eq!(heroes[configs::FRONT_LEFT].name, "Champion");
+        assert_eq!(fallen.len(), 1);
+        assert_eq!(fallen[0].name, "Glass");
+        assert_eq!(outcome.survivors, vec![true, false, false, false, false]); // the team, then the replaced
+    }
+
+    #[test]
+    fn test_run_combat_resets_battle_counters() {
+        let mut ability = Ability::new(Statistic::Speed, AbilityTarget::Itself, 1.0, "battle_start");
+        ability.max_activations = Some(1);
+        let mut heroes: Vec<Character> = (0..4).map(|_| Character::builder("Veteran").health(1000).power(100)
+            .attack_type("all").abilities(vec![ability.clone()]).build()).collect();
+        const EXPECTED_KILLS: u32 = 4;
+
+        for _ in 0..2 {
+            let mut villains: Vec<Character> = (0..4).map(|_| Character::builder("Recruit").health(1).power(0)
+                .build()).collect();
+            run_combat(&mut heroes, &mut villains);
+        }
+
+        assert_eq!(heroes.iter().map(|hero| hero.kills).sum::<u32>(), EXPECTED_KILLS);
+        assert_eq!(heroes[configs::FRONT_LEFT].abilities[0].activations, 1);
+        assert_eq!(heroes[configs::FRONT_LEFT].speed(), 2); // fired in both battles, but only the last one counts
+    }
 }
@@ -9,10 +9,27 @@ pub const MINIMUM_DAMAGE: i32 = 10;
 pub const MAXIUMUM_DAMAGE_RESIST: f64 = 0.85;
 pub const MINIMUM_POWER: i32 = 10;
 pub const MAXIMUM_HEALTH: i32 = i32::MAX;
+pub const FLAT_MODIFIERS_FIRST: bool = true; // flat modifiers are scaled by percent of current ones
 
 // Combat IO parameters
 pub const HEALTH_LEVEL_GREEN: i32 = 67;
 pub const HEALTH_LEVEL_YELLOW: i32 = 33;
+pub const DEBUG_TARGETING: bool = false; // print the reasons behind every targeting decision
+
+// Progression parameters
+pub const LEVEL_CAP: u32 = 20;
+pub const XP_CURVE_BASE: i32 = 100; // experience needed to reach level 2
+pub const XP_CURVE_GROWTH: f64 = 1.5; // each level needs this much more experience than the last
+pub const XP_PER_KILL: i32 = 50;
+pub const XP_FOR_SURVIVING: i32 = 20;
+pub const XP_PER_ENEMY_LEVEL: i32 = 10; // awarded for every enemy level, on victory
+
+// Recruitment parameters
+pub const SQUAD_BUDGET: u32 = 100; // points the player can spend on a squad
+
+// Roster parameters
+pub const INJURY_HEALTH_PENALTY: f64 = -0.2; // roster units that die in battle lose this much maximum health
+pub const INJURY_BATTLES: u32 = 3; // battles an injury lasts
 
 // Positioning parameters
 pub const TEAM_SIZE: usize = 5;
@@ -22,3 +39,4 @@ pub const BACK_LEFT: usize = 2;
 pub const BACK_RIGHT: usize = 3;
 #[allow(dead_code)]
 pub const PET: usize = 4;
+pub const BACK_ROW_ADVANCES: bool = false; // when on, the back row steps up once the front row is wiped out
@@ -0,0 +1,132 @@
+/*
+Errors raised while loading (or saving) game data (characters, classes, items, squads, and growth
+tables), so a typo in one YAML file can be reported to the player instead of crashing the game.
+*/
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+
+#[derive(Debug, PartialEq)]
+pub enum GameError {
+    MissingFile { path: String },
+    Io { path: String, message: String }, // the file exists but couldn't be read
+    Parse { path: String, line: Option<usize>, column: Option<usize>, message: String },
+    Validation { path: String, message: String },
+    Write { path: String, message: String } // saving a file failed
+}
+
+pub type GameResult<T> = Result<T, GameError>;
+
+impl GameError {
+    pub fn from_io(path: &str, error: io::Error) -> GameError {
+        match error.kind() {
+            io::ErrorKind::NotFound => GameError::MissingFile {path: path.to_string()},
+            _ => GameError::Io {path: path.to_string(), message: error.to_string()}
+        }
+    }
+
+    // Line and column are 1-based, like an editor's
+    pub fn from_yaml(path: &str, error: serde_yaml::Error) -> GameError {
+        let location = error.location();
+        GameError::Parse {
+            path: path.to_string(),
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: error.to_string()
+        }
+    }
+
+    pub fn validation(path: &str, message: &str) -> GameError {
+        GameError::Validation {path: path.to_string(), message: message.to_string()}
+    }
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GameError::MissingFile {path} => write!(f, "could not find {}", path),
+            GameError::Io {path, message} => write!(f, "could not read {}: {}", path, message),
+            GameError::Parse {path, line: Some(line), column: Some(column), message} =>
+                write!(f, "could not parse {} (line {}, column {}): {}", path, line, column, message),
+            GameError::Parse {path, message, ..} => write!(f, "could not parse {}: {}", path, message),
+            GameError::Validation {path, message} => write!(f, "invalid data in {}: {}", path, message),
+            GameError::Write {path, message} => write!(f, "could not write {}: {}", path, message)
+        }
+    }
+}
+
+impl Error for GameError {}
+
+// Read any YAML data file
+pub fn read_yaml_file<T: DeserializeOwned>(path: &str) -> GameResult<T> {
+    let file = File::open(path).map_err(|error| GameError::from_io(path, error))?;
+    let reader = BufReader::new(file);
+    serde_yaml::from_reader(reader).map_err(|error| GameError::from_yaml(path, error))
+}
+
+// Save any data in the same YAML format it's read from
+pub fn write_yaml_file<T: Serialize>(path: &str, data: &T) -> GameResult<()> {
+    let write_error = |message: String| GameError::Write {path: path.to_string(), message};
+    let file = File::create(path).map_err(|error| write_error(error.to_string()))?;
+    serde_yaml::to_writer(BufWriter::new(file), data).map_err(|error| write_error(error.to_string()))
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_io_missing_file() {
+        let error = io::Error::new(io::ErrorKind::NotFound, "missing");
+        let expected_error = GameError::MissingFile {path: "data/characters/Nobody.yml".to_string()};
+
+        assert_eq!(GameError::from_io("data/characters/Nobody.yml", error), expected_error);
+    }
+
+    #[test]
+    fn test_from_yaml_location() {
+        let yaml_error: serde_yaml::Error = serde_yaml::from_str::<Vec<i32>>("- 1\n- two").unwrap_err();
+
+        let error: GameError = GameError::from_yaml("test.yml", yaml_error);
+
+        match error {
+            GameError::Parse {line, column, ..} => {
+                assert_eq!(line, Some(2));
+                assert_eq!(column, Some(3));
+            },
+            _ => panic!("expected a parse error")
+        }
+    }
+
+    #[test]
+    fn test_read_yaml_file_missing() {
+        let result: GameResult<Vec<i32>> = read_yaml_file("data/characters/Nobody.yml");
+
+        assert_eq!(result, Err(GameError::MissingFile {path: "data/characters/Nobody.yml".to_string()}));
+    }
+
+    #[test]
+    fn test_write_yaml_file_round_trip() {
+        let path = std::env::temp_dir().join("test_write_yaml_file_round_trip.yml");
+        let path: &str = &path.to_string_lossy();
+        let data: Vec<i32> = vec![1, 2, 3];
+
+        assert_eq!(write_yaml_file(path, &data), Ok(()));
+        assert_eq!(read_yaml_file::<Vec<i32>>(path), Ok(data));
+    }
+
+    #[test]
+    fn test_display() {
+        let error = GameError::validation("GoodTank.yml", "maximum health must be positive");
+
+        assert_eq!(error.to_string(), "invalid data in GoodTank.yml: maximum health must be positive");
+    }
+}
@@ -0,0 +1,158 @@
+/*
+Items are equipment characters carry into battle: one weapon, one armor, and one trinket. An item
+grants statistic bonuses (folded into the character's modifiers when equipped, before any auras are
+applied) and can also grant tags, auras, and abilities. Items live in data/items and are referenced
+by file name from a character's equipment list.
+*/
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::character;
+use crate::character::Character;
+use crate::error;
+use crate::error::GameResult;
+use crate::modifiers::Ability;
+use crate::modifiers::Apply;
+use crate::modifiers::Aura;
+use crate::modifiers::ModifierMode;
+use crate::modifiers::Statistic;
+use crate::tags::Tag;
+use crate::validation;
+use crate::validation::ValidationReport;
+
+// Equipment slots (a character holds at most one item per slot)
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Slot {
+    Weapon,
+    Armor,
+    Trinket
+}
+
+// A change to one statistic for as long as the item is equipped
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatBonus {
+    pub statistic: Statistic,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModifierMode>
+}
+
+impl StatBonus {
+    pub fn new(statistic: Statistic, value: f64) -> StatBonus {
+        StatBonus {statistic, value, mode: None}
+    }
+}
+
+impl Apply for StatBonus {
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn declared_mode(&self) -> Option<ModifierMode> {
+        self.mode
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Item {
+    pub name: String,
+    pub slot: Slot,
+    #[serde(default)]
+    pub bonuses: Vec<StatBonus>,
+    #[serde(default)]
+    pub tags: Vec<Tag>,
+    #[serde(default)]
+    pub auras: Vec<Aura>,
+    #[serde(default)]
+    pub abilities: Vec<Ability>,
+    #[serde(default)]
+    pub description: String
+}
+
+impl Item {
+    pub fn new(name: &str, slot: Slot) -> Item {
+        Item {name: name.to_string(), slot, bonuses: Vec::new(), tags: Vec::new(), auras: Vec::new(),
+            abilities: Vec::new(), description: String::new()}
+    }
+
+    // Items are validated like characters, since the abilities and auras they grant end up on one
+    pub fn new_from_file(filepath: &str) -> GameResult<Self> {
+        let item: Item = error::read_yaml_file(filepath)?;
+        let report: ValidationReport = validation::validate_item(&item, filepath);
+        report.print_warnings();
+        report.into_result()?;
+        Ok(item)
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Items: data/characters/ => data/items/
+pub fn items_directory(directory_characters: &str) -> String {
+    format!("{}/", character::data_directory(Path::new(directory_characters), "items").display())
+}
+
+// Equip every item on the character's equipment list
+pub fn equip_from_directory(character: &mut Character, directory_items: &str) -> GameResult<()> {
+    for item_name in character.equipment.clone().iter() {
+        let item_path: &str = &format!("{}{}.yml", directory_items, item_name);
+        character.equip(Item::new_from_file(item_path)?);
+    }
+    Ok(())
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHARACTER_FOLDER: &str = "data/characters/";
+    const ITEM_FOLDER: &str = "data/items/";
+
+    #[test]
+    fn test_item_from_file() {
+        let mut expected_item = Item::new("Tower Shield", Slot::Armor);
+        expected_item.bonuses = vec![StatBonus::new(Statistic::Dr, 0.1), StatBonus::new(Statistic::Speed, -1.0)];
+        expected_item.tags = vec![Tag::Taunt];
+
+        let item: Item = Item::new_from_file(&format!("{}TowerShield.yml", ITEM_FOLDER)).unwrap();
+
+        assert_eq!(item.name, expected_item.name);
+        assert_eq!(item.slot, expected_item.slot);
+        assert_eq!(item.bonuses, expected_item.bonuses);
+        assert_eq!(item.tags, expected_item.tags);
+    }
+
+    #[test]
+    fn test_equip_from_directory_missing_item() {
+        let mut character = Character::builder("Dave").class("Warrior").speed(0).power(50).critical_chance(0.1).build();
+        character.equipment = vec!["Excalibur".to_string()];
+
+        assert!(equip_from_directory(&mut character, ITEM_FOLDER).is_err());
+    }
+
+    #[test]
+    fn test_items_directory() {
+        assert_eq!(items_directory(CHARACTER_FOLDER), ITEM_FOLDER);
+    }
+
+    #[test]
+    fn test_equip_from_directory() {
+        let mut character = Character::new_from_file(&format!("{}GoodTank.yml", CHARACTER_FOLDER)).unwrap();
+        const EXPECTED_SPEED: i32 = -2;
+        const EXPECTED_DR: f64 = 0.1;
+
+        equip_from_directory(&mut character, ITEM_FOLDER).unwrap();
+
+        assert_eq!(character.items.len(), 1);
+        assert_eq!(character.speed(), EXPECTED_SPEED);
+        assert_eq!(character.dr(), EXPECTED_DR);
+        assert!(character.tags.contains(&Tag::Taunt));
+    }
+}
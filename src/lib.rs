@@ -0,0 +1,20 @@
+/*
+The game's modules, for use as a library (main.rs runs the game on top of them).
+*/
+
+pub mod configs;
+pub mod character;
+pub mod combat;
+pub mod error;
+pub mod input;
+pub mod items;
+pub mod modifiers;
+pub mod progression;
+pub mod recruitment;
+pub mod roster;
+pub mod targeting;
+pub mod squad;
+pub mod tags;
+#[cfg(test)]
+mod test_helpers;
+pub mod validation;
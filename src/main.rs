@@ -1,20 +1,12 @@
-#![allow(dead_code)]
-
 // Imports => external packages
 use colored::*;
 use std::env;
 use std::path::Path;
+use std::process;
 
 // Imports => internal packages
-mod configs;
-mod character;
-mod combat;
-mod input;
-mod modifiers;
-mod targeting;
-mod squad;
-
-use character::Character;
+use rust_strategy_rpg::{combat, configs, input, progression, roster, squad};
+use rust_strategy_rpg::character::Character;
 
 fn main() {
     print!("{esc}[2J{esc}[1;1H", esc = 27 as char);
@@ -26,20 +18,58 @@ fn main() {
     // let hero_filepath: String = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/squad/test_hero_squad.yml".to_string();
     let villain_filepath: String = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/squad/test_villain_squad.yml".to_string();
     let character_folder = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/characters/";
+    let growth_filepath = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/growth/class_growth.yml";
+    let roster_squad_filepath: String = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/squad/test_roster_squad.yml".to_string();
+    let roster_filepath = "/Users/Presidente/Documents/Programming/Games/hero_battle/data/roster/roster.yml";
 
-    let mut villains: Vec<Character> = squad::squad_from_file(villain_filepath, &character_folder);
+    let (mut villains, mut villain_reserves) = match squad::squad_with_reserves_from_file(villain_filepath, character_folder) {
+        Ok(squad) => squad,
+        Err(error) => {
+            println!("{}", error);
+            process::exit(1);
+        }
+    };
     println!("You must defeat a squad of evil-doers consisting of the following characters: ");
     for villains in villains.iter() {
         villains.print_pretty_stats();
     }
 
     println!("You can choose from the following characters:\n - GoodArcherRogue\n - GoodMeleeRogue\n - GoodSwordsman\n - GoodTank");
+    println!("You have {} points to spend on your squad.", configs::SQUAD_BUDGET);
 
     // let mut heroes: Vec<Character> = squad::squad_from_file(hero_filepath, &character_folder);
-    let mut heroes: Vec<Character> = squad::squad_from_input(&character_folder);
+    // Roster units keep their levels and injuries between battles; a chosen squad starts fresh
+    let use_roster: bool = input::grab_input("Send in your roster squad instead? (y/n)").eq_ignore_ascii_case("y");
+    let (mut heroes, mut hero_reserves): (Vec<Character>, Vec<Character>) = if use_roster {
+        match squad::squad_with_reserves_from_file(roster_squad_filepath, character_folder) {
+            Ok(squad) => squad,
+            Err(error) => {
+                println!("{}", error);
+                process::exit(1);
+            }
+        }
+    } else {
+        (squad::squad_from_input(character_folder), Vec::new())
+    };
     // for hero in heroes.iter() {
     //     hero.print_pretty_stats();
     // }
+    squad::apply_battle_auras(&mut heroes, &mut villains);
 
-    combat::run_combat(&mut heroes, &mut villains);
+    let (outcome, mut heroes_fallen) = combat::run_combat_with_reserves(&mut heroes, &mut hero_reserves, &mut villains, &mut villain_reserves);
+    match progression::growth_tables_from_file(growth_filepath) {
+        Ok(growth_tables) => {
+            progression::award_xp(&mut heroes, &mut heroes_fallen, &outcome, &growth_tables);
+        },
+        Err(error) => println!("{}", error)
+    }
+    if use_roster {
+        let saved = roster::Roster::new_from_file(roster_filepath).and_then(|mut roster| {
+            roster.record_battle(&heroes, &heroes_fallen);
+            roster.save_to_file(roster_filepath)
+        });
+        if let Err(error) = saved {
+            println!("{}", error);
+        }
+    }
 }
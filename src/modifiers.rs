@@ -1,75 +1,267 @@
 /*
 There are two kinds of modifiers: passive auras which are applied before combat begins, and active
-abilities which are applied during combat.
+abilities which are applied during combat. Position modifiers are passive bonuses that only apply
+while a character stands in a given row or column. All of them turn into Modifiers that sit on top
+of a character's base statistics (see Character::add_modifier).
 
 File structure:
+- Statistic, AuraTarget, and AbilityTarget enums
+- Modifier (struct) definition and impl
 - Apply trait
 - Aura (struct) definition, impl, and Apply impl
-- Ability (struct) definition, impl, and Apply impl
+- Placement (enum), PositionModifier (struct) definition and impl
+- Condition (enum) and TriggerContext (struct) for ability triggers
+- AbilityEffect (struct) definition, impl, and Apply impl
+- Ability (struct) definition and impl: a named group of effects that fire together
 - Tests
     - Aura tests
     - Ability tests
 */
 
 use serde::Deserialize;
+use serde::Serialize;
+
+use crate::tags::Tag;
+use crate::targeting;
+
+// Every statistic a modifier can change (names match the YAML data files)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Statistic {
+    #[serde(rename = "health")]
+    Health,
+    #[serde(rename = "power")]
+    Power,
+    #[serde(rename = "critical chance")]
+    CriticalChance,
+    #[serde(rename = "speed")]
+    Speed,
+    #[serde(rename = "dt")]
+    Dt,
+    #[serde(rename = "dr")]
+    Dr
+}
+
+// Who receives an aura. Filtered targets are written as a map in YAML, e.g. `allies_with_tag: undead`
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AuraTarget {
+    #[serde(rename = "self")]
+    Itself,
+    #[serde(rename = "allies")]
+    Allies,
+    #[serde(rename = "allies_with_tag")]
+    AlliesWithTag(Tag),
+    #[serde(rename = "allies_of_class")]
+    AlliesOfClass(String),
+    #[serde(rename = "front_row")]
+    FrontRow, // allies in the front row
+    #[serde(rename = "adjacent")]
+    Adjacent, // allies adjacent to the aura's carrier
+    #[serde(rename = "enemies")]
+    Enemies // pre-battle debuff on the opposing squad
+}
+
+// Who receives an ability's effect
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AbilityTarget {
+    #[serde(rename = "self")]
+    Itself,
+    #[serde(rename = "allies")]
+    Allies,
+    #[serde(rename = "enemies")]
+    Enemies,
+    #[serde(rename = "attacked")]
+    Attacked, // the enemies hit by this turn's attack
+    #[serde(rename = "attacker")]
+    Attacker, // the enemy whose attack set off an "attacked" trigger
+    #[serde(rename = "lowest_health_ally")]
+    LowestHealthAlly, // the living ally with the smallest fraction of their maximum health
+    #[serde(rename = "random_enemy")]
+    RandomEnemy // a living enemy chosen at random
+}
+
+impl AbilityTarget {
+    // The trigger event a target depends on (attacked and attacker only exist for one event each)
+    pub fn trigger(&self) -> Option<&'static str> {
+        match self {
+            AbilityTarget::Attacked => Some("attack"),
+            AbilityTarget::Attacker => Some("attacked"),
+            _ => None
+        }
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// How a modifier's value changes a statistic. Modes are stacked in a fixed order: flat and percent
+// of base changes first (or percent first, see configs::FLAT_MODIFIERS_FIRST), then percent of
+// current changes, and finally set-to values (the most recent one wins).
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ModifierMode {
+    #[serde(rename = "flat")]
+    Flat, // value is added as-is ("+500 power", "+0.05 critical chance")
+    #[serde(rename = "percent of base")]
+    PercentOfBase, // value is a fraction of the base statistic (0.2 => +20% of base)
+    #[serde(rename = "percent of current")]
+    PercentOfCurrent, // value is a fraction of the statistic after flat and base changes (compounds)
+    #[serde(rename = "set")]
+    Set // the statistic is set to value
+}
+
+// An active change to one of a character's statistics
+#[derive(Clone, Debug, PartialEq)]
+pub struct Modifier {
+    pub source: String, // what granted the modifier, e.g. "aura: The Good Tank" or "formation"
+    pub statistic: Statistic,
+    pub mode: ModifierMode,
+    pub value: f64,
+    pub duration: Option<u32> // rounds remaining (None lasts for the rest of the battle)
+}
+
+impl Modifier {
+    pub fn new(source: &str, statistic: Statistic, mode: ModifierMode, value: f64, duration: Option<u32>) -> Modifier {
+        Modifier {source: source.to_string(), statistic, mode, value, duration}
+    }
+}
+
+// Speed and damage reduction are flat by default, everything else is a percentage of the base value
+pub fn default_mode(statistic: Statistic) -> ModifierMode {
+    match statistic {
+        Statistic::Speed | Statistic::Dr => ModifierMode::Flat,
+        _ => ModifierMode::PercentOfBase
+    }
+}
 
-use crate::configs;
+/* --------------------------------------------------------------------------------------------- */
 
 pub trait Apply {
-    fn convert_and_add<T: Into<f64> + Copy>(&self, base_value: T) -> f64;
-    fn convert_and_multiply<T: Into<f64> + Copy>(&self, base_value: T) -> f64;
-    fn change_health(&self, base_value: i32, max_value: i32) -> i32;
-    fn change_power(&self, base_value: i32) -> i32;
-    fn change_crit_chance(&self, base_value: f64) -> f64;
-    fn change_speed(&self, base_value: i32) -> i32;
+    fn value(&self) -> f64;
+
+    // Number of rounds the change lasts (None lasts for the rest of the battle)
+    fn duration(&self) -> Option<u32> {
+        None
+    }
+
+    // Mode declared in the data (None falls back to the statistic's default mode)
+    fn declared_mode(&self) -> Option<ModifierMode>;
+
+    fn mode(&self, statistic: Statistic) -> ModifierMode {
+        self.declared_mode().unwrap_or_else(|| default_mode(statistic))
+    }
+
+    fn to_modifier(&self, statistic: Statistic, source: &str) -> Modifier {
+        Modifier::new(source, statistic, self.mode(statistic), self.value(), self.duration())
+    }
 }
 
 /* --------------------------------------------------------------------------------------------- */
 
+// How auras sharing an id combine on the same character (auras without an id always stack)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Stacking {
+    #[default]
+    #[serde(rename = "always")]
+    Always,
+    #[serde(rename = "unique")]
+    Unique, // only the strongest value applies (largest in size, so debuffs keep the harshest)
+    #[serde(rename = "up_to")]
+    UpTo(usize) // the N strongest values apply
+}
+
+impl Stacking {
+    // Maximum number of auras with the same id that apply (None if unlimited)
+    pub fn limit(&self) -> Option<usize> {
+        match self {
+            Stacking::Always => None,
+            Stacking::Unique => Some(1),
+            Stacking::UpTo(count) => Some(*count)
+        }
+    }
+}
+
 // Used by Character struct
-#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Aura {
-    pub statistic: String,
-    pub target: String,
-    pub value: f64
+    pub statistic: Statistic,
+    pub target: AuraTarget,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModifierMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>, // auras with the same id are subject to the stacking policy
+    #[serde(default)]
+    pub stacking: Stacking
 }
 
 impl Aura {
-    pub fn new(statistic: &str, target: &str, value: f64) -> Aura {
-        Aura {statistic: statistic.to_string(), target: target.to_string(), value: value}
+    pub fn new(statistic: Statistic, target: AuraTarget, value: f64) -> Aura {
+        Aura {statistic, target, value, mode: None, id: None, stacking: Stacking::Always}
+    }
+
+    pub fn new_unique(statistic: Statistic, target: AuraTarget, value: f64, id: &str, stacking: Stacking) -> Aura {
+        Aura {statistic, target, value, mode: None, id: Some(id.to_string()), stacking}
     }
 }
 
 impl Apply for Aura {
-    // Take input (i32 or f64), convert to f64, multiply, then return
-    fn convert_and_multiply<T: Into<f64> + Copy>(&self, base_value: T) -> f64 {
-        return base_value.into() * (1.0 + self.value)
+    fn value(&self) -> f64 {
+        self.value
     }
 
-    // Take input (i32 or f64), convert to f64, add, then return
-    fn convert_and_add<T: Into<f64> + Copy>(&self, base_value: T) -> f64 {
-        return base_value.into() + self.value
+    fn declared_mode(&self) -> Option<ModifierMode> {
+        self.mode
     }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// The squad positions a position modifier applies to (names match the YAML data files)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Placement {
+    #[serde(rename = "front row")]
+    FrontRow,
+    #[serde(rename = "back row")]
+    BackRow,
+    #[serde(rename = "left column")]
+    LeftColumn,
+    #[serde(rename = "right column")]
+    RightColumn
+}
+
+// Used by Character struct and SquadConstructor (squad-wide formation bonuses)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PositionModifier {
+    pub statistic: Statistic,
+    pub position: Placement,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModifierMode>
+}
 
-    // Auras don't need a reference to maximum health because they can change it (abilities will need a cap)
-    fn change_health(&self, base_value: i32, _max_value: i32) -> i32 {
-        return self.convert_and_multiply(base_value).round() as i32
+impl PositionModifier {
+    pub fn new(statistic: Statistic, position: Placement, value: f64) -> PositionModifier {
+        PositionModifier {statistic, position, value, mode: None}
     }
 
-    // Power is uncapped
-    fn change_power(&self, base_value: i32) -> i32 {
-        return self.convert_and_multiply(base_value).round() as i32
+    // Check whether the modifier is active for a character standing at the given position
+    pub fn applies_at(&self, position: usize) -> bool {
+        match self.position {
+            Placement::FrontRow => targeting::is_front_row(position),
+            Placement::BackRow => targeting::is_back_row(position),
+            Placement::LeftColumn => targeting::is_left_column(position),
+            Placement::RightColumn => targeting::is_right_column(position)
+        }
     }
+}
 
-    // Crit chance is capped at 99%
-    fn change_crit_chance(&self, base_value: f64) -> f64 {
-        let new_crit_chance: f64 = self.convert_and_multiply(base_value);
-        return new_crit_chance.min(configs::CRITICAL_CHANCE_CAP)
+impl Apply for PositionModifier {
+    fn value(&self) -> f64 {
+        self.value
     }
 
-    // Speed is uncapped
-    fn change_speed(&self, base_value: i32) -> i32 {
-        return self.convert_and_add(base_value).round() as i32
+    fn declared_mode(&self) -> Option<ModifierMode> {
+        self.mode
     }
 }
 
@@ -82,70 +274,174 @@ impl Apply for Aura {
 // Also need to establish a trigger for Abilities -> anything self is easy, allies is harder
 //      - Attack & attacked, killed & died
 
-// Used by Character struct
-#[derive(Clone, Debug, PartialEq, Deserialize)]
-pub struct Ability {
-    pub statistic: String,
-    pub target: String,
+// Abilities can move characters around the squad instead of (or as well as) changing a statistic
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Movement {
+    Push, // swap the targeted enemies (attacked or attacker) into their front row
+    Retreat // swap self into the back row
+}
+
+// Extra requirements for an ability to fire, checked alongside its trigger
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    #[serde(rename = "self_health_below")]
+    SelfHealthBelow(f64), // fraction of maximum health
+    #[serde(rename = "target_has_tag")]
+    TargetHasTag(Tag), // any of the attacked characters carries the tag
+    #[serde(rename = "allies_alive_below")]
+    AlliesAliveBelow(usize) // living members of the squad (including self)
+}
+
+// State of the battle an ability's conditions are checked against
+#[derive(Clone, Debug, PartialEq)]
+pub struct TriggerContext {
+    pub health_fraction: f64,
+    pub target_tags: Vec<Tag>,
+    pub allies_alive: usize
+}
+
+impl Condition {
+    pub fn is_met(&self, context: &TriggerContext) -> bool {
+        match self {
+            Condition::SelfHealthBelow(fraction) => context.health_fraction < *fraction,
+            Condition::TargetHasTag(tag) => context.target_tags.contains(tag),
+            Condition::AlliesAliveBelow(count) => context.allies_alive < *count
+        }
+    }
+}
+
+// What an ability effect does to its recipients
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum EffectKind {
+    #[default]
+    #[serde(rename = "modifier")]
+    Modifier, // change a statistic other than health (see MaxHealth)
+    #[serde(rename = "heal")]
+    Heal, // restore current health, up to maximum health
+    #[serde(rename = "damage")]
+    Damage, // deal direct damage, reduced by armor unless bypass_armor is set
+    #[serde(rename = "max_health")]
+    MaxHealth // change maximum health (current health moves by the same amount)
+}
+
+fn default_chance() -> f64 {
+    1.0
+}
+
+// One of the things an ability does when it fires (misspelled fields are rejected when loading)
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbilityEffect {
+    #[serde(default)]
+    pub kind: EffectKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statistic: Option<Statistic>, // only used by modifier effects
+    pub target: AbilityTarget,
+    #[serde(default)]
     pub value: f64,
-    pub trigger_event: String
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModifierMode>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u32>, // rounds the change lasts (omit to last for the rest of the battle)
+    #[serde(default)]
+    pub bypass_armor: bool, // damage effects ignore damage threshold and resistance
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movement: Option<Movement> // movement effects don't change a statistic
 }
 
-impl Ability {
-    pub fn new(statistic: &str, target: &str, value: f64, event_trigger: &str) -> Ability {
-        Ability {statistic: statistic.to_string(), target: target.to_string(), value: value,
-            trigger_event: event_trigger.to_string()}
+impl AbilityEffect {
+    pub fn new(statistic: Statistic, target: AbilityTarget, value: f64) -> AbilityEffect {
+        AbilityEffect {kind: EffectKind::Modifier, statistic: Some(statistic), target, value, mode: None,
+            duration: None, bypass_armor: false, movement: None}
     }
 
-    pub fn check_ability_trigger(&self, event_trigger: &str) -> bool {
-        if event_trigger.to_string() == self.trigger_event {
-            return true
-        } else {
-            return false
+    // Heals and damage, e.g. "heal 25%" (value 0.25, scale is the recipient's maximum health) or
+    // "deal 50% of power" (value 0.5, scale is the user's power)
+    pub fn new_kind(kind: EffectKind, target: AbilityTarget, value: f64) -> AbilityEffect {
+        AbilityEffect {kind, statistic: None, target, value, mode: None, duration: None, bypass_armor: false,
+            movement: None}
+    }
+
+    pub fn new_movement(movement: Movement, target: AbilityTarget) -> AbilityEffect {
+        AbilityEffect {kind: EffectKind::Modifier, statistic: None, target, value: 0.0, mode: None, duration: None,
+            bypass_armor: false, movement: Some(movement)}
+    }
+
+    // Health restored or damage dealt: a fraction of the scale, or the value itself for flat effects
+    pub fn amount(&self, scale: i32) -> i32 {
+        match self.mode {
+            Some(ModifierMode::Flat) => self.value.round() as i32,
+            _ => (self.value * scale as f64).round() as i32
         }
     }
 }
 
-impl Apply for Ability {
-    // Take input (i32 or f64), convert to f64, multiply, then return
-    fn convert_and_multiply<T: Into<f64> + Copy>(&self, base_value: T) -> f64 {
-        return base_value.into() * (1.0 + self.value)
+impl Apply for AbilityEffect {
+    fn value(&self) -> f64 {
+        self.value
     }
 
-    // Take input (i32 or f64), convert to f64, add, then return
-    fn convert_and_add<T: Into<f64> + Copy>(&self, base_value: T) -> f64 {
-        return base_value.into() + self.value
+    fn declared_mode(&self) -> Option<ModifierMode> {
+        self.mode
     }
 
-    // Health is bounded to starting health to prevent endless battles
-    fn change_health(&self, base_value: i32, max_value: i32) -> i32 {
-        let new_health: i32 = self.convert_and_multiply(base_value).round() as i32;
-        if new_health <= max_value {
-            return new_health
-        } else {
-            return max_value
-        }
+    fn duration(&self) -> Option<u32> {
+        self.duration
     }
+}
 
-    // Power is uncapped but bounded below
-    fn change_power(&self, base_value: i32) -> i32 {
-        let new_power: i32 = self.convert_and_multiply(base_value).round() as i32;
-        if new_power < configs::MINIMUM_POWER {
-            return configs::MINIMUM_POWER
-        } else {
-            return new_power
-        }
+// Used by Character struct. All of an ability's effects happen together when it fires.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Ability {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub trigger_event: String,
+    pub effects: Vec<AbilityEffect>,
+    #[serde(default = "default_chance")]
+    pub chance: f64, // probability the ability fires when triggered
+    #[serde(default)]
+    pub conditions: Vec<Condition>, // all must hold for the ability to fire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_activations: Option<u32>, // per battle (omit for no limit)
+    #[serde(skip)]
+    pub activations: u32
+}
+
+impl Ability {
+    pub fn new_composite(name: &str, description: &str, event_trigger: &str, effects: Vec<AbilityEffect>) -> Ability {
+        Ability {name: name.to_string(), description: description.to_string(), trigger_event: event_trigger.to_string(),
+            effects, chance: 1.0, conditions: Vec::new(), max_activations: None, activations: 0}
+    }
+
+    // Unnamed abilities with a single effect
+    pub fn new(statistic: Statistic, target: AbilityTarget, value: f64, event_trigger: &str) -> Ability {
+        Ability::new_composite("", "", event_trigger, vec![AbilityEffect::new(statistic, target, value)])
     }
 
-    // Crit chance is capped at 99%
-    fn change_crit_chance(&self, base_value: f64) -> f64 {
-        let new_crit_chance: f64 = self.convert_and_multiply(base_value);
-        return new_crit_chance.min(configs::CRITICAL_CHANCE_CAP)
+    pub fn new_kind(kind: EffectKind, target: AbilityTarget, value: f64, event_trigger: &str) -> Ability {
+        Ability::new_composite("", "", event_trigger, vec![AbilityEffect::new_kind(kind, target, value)])
     }
 
-    // Speed is uncapped
-    fn change_speed(&self, base_value: i32) -> i32 {
-        return self.convert_and_add(base_value).round() as i32
+    pub fn new_movement(movement: Movement, target: AbilityTarget, event_trigger: &str) -> Ability {
+        Ability::new_composite("", "", event_trigger, vec![AbilityEffect::new_movement(movement, target)])
+    }
+
+    // An ability fires when its trigger matches, it has activations left, all of its conditions hold,
+    // and the roll (uniform in [0, 1)) falls under its chance
+    pub fn check_ability_trigger(&self, event_trigger: &str, context: &TriggerContext, roll: f64) -> bool {
+        if event_trigger != self.trigger_event {
+            return false
+        }
+        if let Some(max_activations) = self.max_activations {
+            if self.activations >= max_activations {
+                return false
+            }
+        }
+        self.conditions.iter().all(|condition| condition.is_met(context)) && roll < self.chance
     }
 }
 
@@ -154,89 +450,323 @@ impl Apply for Ability {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::character::Character;
+    use crate::configs;
 
     // Aura struct tests
     #[test]
-    fn test_aura_convert_and_add_i32() {
-        const INPUT_BASE: i32 = 1;
+    fn test_aura_to_modifier_flat() {
         const VALUE: f64 = 2.0;
-        let test_aura = Aura::new("speed", "self", VALUE);
-        const EXPECTED_VALUE: f64 = 3.0;
+        let test_aura = Aura::new(Statistic::Speed, AuraTarget::Itself, VALUE);
+        let expected_modifier = Modifier::new("aura", Statistic::Speed, ModifierMode::Flat, VALUE, None);
 
-        let new_value = test_aura.convert_and_add(INPUT_BASE);
+        let modifier = test_aura.to_modifier(Statistic::Speed, "aura");
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(modifier, expected_modifier);
     }
 
     #[test]
-    fn test_aura_convert_and_multiply_i32() {
-        const INPUT_BASE: i32 = 100;
+    fn test_aura_to_modifier_percent_of_base() {
         const VALUE: f64 = 0.4;
-        let test_aura = Aura::new("health", "self", VALUE);
-        const EXPECTED_VALUE: f64 = 140.0;
+        let test_aura = Aura::new(Statistic::Health, AuraTarget::Itself, VALUE);
+        let expected_modifier = Modifier::new("aura", Statistic::Health, ModifierMode::PercentOfBase, VALUE, None);
 
-        let new_value = test_aura.convert_and_multiply(INPUT_BASE);
+        let modifier = test_aura.to_modifier(Statistic::Health, "aura");
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(modifier, expected_modifier);
     }
 
     #[test]
-    fn test_aura_convert_and_multiply_f64() {
-        const INPUT_BASE: f64 = 100.0;
-        const VALUE: f64 = 0.4;
-        let test_aura = Aura::new("health", "self", VALUE);
-        const EXPECTED_VALUE: f64 = 140.0;
+    fn test_aura_to_modifier_dr_flat() {
+        let test_aura = Aura::new(Statistic::Dr, AuraTarget::Itself, 0.2);
 
-        let new_value = test_aura.convert_and_multiply(INPUT_BASE);
+        assert_eq!(test_aura.mode(Statistic::Dr), ModifierMode::Flat);
+    }
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+    fn aura_test_character(health: i32, power: i32, critical_chance: f64, speed: i32) -> Character {
+        Character::builder("Dave").class("Warrior").speed(speed).health(health).power(power)
+            .critical_chance(critical_chance).build()
     }
 
     #[test]
     fn test_aura_change_health() {
-        const INPUT_BASE: i32 = 100;
         const VALUE: f64 = 0.4;
-        let test_aura = Aura::new("health", "self", VALUE);
+        let test_aura = Aura::new(Statistic::Health, AuraTarget::Itself, VALUE);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
         const EXPECTED_VALUE: i32 = 140;
 
-        let new_value = test_aura.change_health(INPUT_BASE, configs::MAXIMUM_HEALTH);
+        test_character.apply_modifier(Statistic::Health, &test_aura, "aura");
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(test_character.health_max(), EXPECTED_VALUE);
+        assert_eq!(test_character.health, EXPECTED_VALUE);
     }
 
     #[test]
     fn test_aura_change_power() {
-        const INPUT_BASE: i32 = 100;
         const VALUE: f64 = 0.4;
-        let test_aura = Aura::new("health", "self", VALUE);
+        let test_aura = Aura::new(Statistic::Power, AuraTarget::Itself, VALUE);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
         const EXPECTED_VALUE: i32 = 140;
 
-        let new_value = test_aura.change_power(INPUT_BASE);
+        test_character.apply_modifier(Statistic::Power, &test_aura, "aura");
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(test_character.power(), EXPECTED_VALUE);
     }
 
     #[test]
     fn test_aura_change_crit_chance() {
-        const INPUT_BASE: f64 = 0.25;
         const VALUE: f64 = 0.2;
-        let test_aura = Aura::new("critical chance", "self", VALUE);
+        let test_aura = Aura::new(Statistic::CriticalChance, AuraTarget::Itself, VALUE);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
         const EXPECTED_VALUE: f64 = 0.3;
 
-        let new_value = test_aura.change_crit_chance(INPUT_BASE);
+        test_character.apply_modifier(Statistic::CriticalChance, &test_aura, "aura");
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(test_character.critical_chance(), EXPECTED_VALUE);
+    }
+
+    #[test]
+    fn test_aura_change_crit_chance_capped() {
+        const VALUE: f64 = 10.0;
+        let test_aura = Aura::new(Statistic::CriticalChance, AuraTarget::Itself, VALUE);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
+
+        test_character.apply_modifier(Statistic::CriticalChance, &test_aura, "aura");
+
+        assert_eq!(test_character.critical_chance(), configs::CRITICAL_CHANCE_CAP);
     }
 
     #[test]
     fn test_aura_change_speed() {
-        const INPUT_BASE: i32 = 0;
         const VALUE: f64 = 2.0;
-        let test_aura = Aura::new("speed", "self", VALUE);
+        let test_aura = Aura::new(Statistic::Speed, AuraTarget::Itself, VALUE);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
         const EXPECTED_VALUE: i32 = 2;
 
-        let new_value = test_aura.change_speed(INPUT_BASE);
+        test_character.apply_modifier(Statistic::Speed, &test_aura, "aura");
+
+        assert_eq!(test_character.speed(), EXPECTED_VALUE);
+    }
+
+    #[test]
+    fn test_aura_declared_mode() {
+        let yaml: &str = "statistic: \"power\"\ntarget: \"allies\"\nvalue: 500\nmode: \"flat\"";
+        let mut test_character = aura_test_character(100, 1000, 0.25, 0);
+        const EXPECTED_VALUE: i32 = 1500;
+
+        let test_aura: Aura = serde_yaml::from_str(yaml).unwrap();
+        test_character.apply_modifier(Statistic::Power, &test_aura, "aura");
+
+        assert_eq!(test_aura.mode(Statistic::Power), ModifierMode::Flat);
+        assert_eq!(test_character.power(), EXPECTED_VALUE);
+    }
+
+    // Ability struct tests
+    #[test]
+    fn test_ability_change_power_bounded_below() {
+        let test_ability = AbilityEffect::new(Statistic::Power, AbilityTarget::Enemies, -0.99);
+        let mut test_character = aura_test_character(100, 100, 0.25, 0);
+
+        test_character.apply_modifier(Statistic::Power, &test_ability, "ability");
+
+        assert_eq!(test_character.power(), configs::MINIMUM_POWER);
+    }
+
+    #[test]
+    fn test_ability_to_modifier_duration() {
+        let mut test_ability = AbilityEffect::new(Statistic::Power, AbilityTarget::Allies, 0.1);
+        test_ability.duration = Some(2);
+        let expected_modifier = Modifier::new("ability", Statistic::Power, ModifierMode::PercentOfBase, 0.1, Some(2));
+
+        let modifier = test_ability.to_modifier(Statistic::Power, "ability");
+
+        assert_eq!(modifier, expected_modifier);
+    }
+
+    // Ability trigger tests
+    fn trigger_test_context() -> TriggerContext {
+        TriggerContext {health_fraction: 0.4, target_tags: vec![Tag::Undead], allies_alive: 2}
+    }
+
+    #[test]
+    fn test_ability_check_trigger() {
+        let test_ability = Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack");
+
+        assert!(test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.99));
+        assert!(!test_ability.check_ability_trigger("battle_start", &trigger_test_context(), 0.0));
+    }
+
+    #[test]
+    fn test_ability_check_trigger_chance() {
+        let mut test_ability = Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack");
+        test_ability.chance = 0.25;
+
+        assert!(test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.2));
+        assert!(!test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.3));
+    }
+
+    #[test]
+    fn test_ability_check_trigger_conditions() {
+        let mut test_ability = Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack");
+        test_ability.conditions = vec![Condition::SelfHealthBelow(0.5), Condition::TargetHasTag(Tag::Undead),
+            Condition::AlliesAliveBelow(3)];
+        let mut healthy_context: TriggerContext = trigger_test_context();
+        healthy_context.health_fraction = 0.8;
+
+        assert!(test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.0));
+        assert!(!test_ability.check_ability_trigger("attack", &healthy_context, 0.0));
+    }
+
+    #[test]
+    fn test_ability_check_trigger_max_activations() {
+        let mut test_ability = Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "attack");
+        test_ability.max_activations = Some(1);
+
+        assert!(test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.0));
+        test_ability.activations = 1;
+        assert!(!test_ability.check_ability_trigger("attack", &trigger_test_context(), 0.0));
+    }
+
+    #[test]
+    fn test_ability_deserialize_conditions() {
+        let yaml: &str = "trigger_event: \"attack\"\neffects:\n  - statistic: \"power\"\n    target: \"self\"\n    value: 0.5\n\
+            chance: 0.5\nconditions:\n  - self_health_below: 0.3\nmax_activations: 1";
+        let mut expected_ability = Ability::new(Statistic::Power, AbilityTarget::Itself, 0.5, "attack");
+        expected_ability.chance = 0.5;
+        expected_ability.conditions = vec![Condition::SelfHealthBelow(0.3)];
+        expected_ability.max_activations = Some(1);
+
+        let test_ability: Ability = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(test_ability, expected_ability);
+    }
+
+    // AbilityEffect struct tests
+    #[test]
+    fn test_ability_effect_amount() {
+        let mut test_effect = AbilityEffect::new_kind(EffectKind::Heal, AbilityTarget::Allies, 0.25);
+        const EXPECTED_PERCENT: i32 = 250;
+        const EXPECTED_FLAT: i32 = 300;
+
+        assert_eq!(test_effect.amount(1000), EXPECTED_PERCENT);
+        test_effect.mode = Some(ModifierMode::Flat);
+        test_effect.value = 300.0;
+        assert_eq!(test_effect.amount(1000), EXPECTED_FLAT);
+    }
+
+    #[test]
+    fn test_ability_effect_deserialize() {
+        let yaml: &str = "kind: \"damage\"\nbypass_armor: true\ntarget: \"random_enemy\"\nvalue: 0.5";
+        let mut expected_effect = AbilityEffect::new_kind(EffectKind::Damage, AbilityTarget::RandomEnemy, 0.5);
+        expected_effect.bypass_armor = true;
+
+        let test_effect: AbilityEffect = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(test_effect, expected_effect);
+    }
+
+    // PositionModifier struct tests
+    #[test]
+    fn test_position_modifier_applies_at() {
+        let front = PositionModifier::new(Statistic::Dr, Placement::FrontRow, 0.2);
+        let back = PositionModifier::new(Statistic::CriticalChance, Placement::BackRow, 0.1);
+
+        assert!(front.applies_at(configs::FRONT_RIGHT));
+        assert!(!front.applies_at(configs::BACK_RIGHT));
+        assert!(back.applies_at(configs::BACK_LEFT));
+        assert!(!back.applies_at(configs::PET));
+    }
+
+    #[test]
+    fn test_position_modifier_deserialize() {
+        let yaml: &str = "statistic: \"dr\"\nposition: \"left column\"\nvalue: 0.1";
+        let expected_modifier = PositionModifier::new(Statistic::Dr, Placement::LeftColumn, 0.1);
+
+        let test_modifier: PositionModifier = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(test_modifier, expected_modifier);
+        assert!(serde_yaml::from_str::<PositionModifier>("statistic: \"dr\"\nposition: \"middle\"\nvalue: 0.1").is_err());
+    }
+
+    // Typed data tests
+    #[test]
+    fn test_aura_deserialize() {
+        let yaml: &str = "statistic: \"critical chance\"\ntarget: \"allies\"\nvalue: 0.2";
+        let expected_aura = Aura::new(Statistic::CriticalChance, AuraTarget::Allies, 0.2);
+
+        let aura: Aura = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(aura, expected_aura);
+    }
+
+    #[test]
+    fn test_aura_deserialize_filtered_target() {
+        let yaml: &str = "statistic: \"power\"\ntarget:\n  allies_with_tag: \"undead\"\nvalue: 0.2";
+        let expected_target = AuraTarget::AlliesWithTag(Tag::Undead);
+
+        let test_aura: Aura = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(test_aura.target, expected_target);
+    }
+
+    #[test]
+    fn test_aura_deserialize_stacking() {
+        let yaml: &str = "statistic: \"health\"\ntarget: \"allies\"\nvalue: 0.4\nid: \"tank health\"\nstacking:\n  up_to: 2";
+        let expected_aura = Aura::new_unique(Statistic::Health, AuraTarget::Allies, 0.4, "tank health", Stacking::UpTo(2));
+
+        let test_aura: Aura = serde_yaml::from_str(yaml).unwrap();
+
+        assert_eq!(test_aura, expected_aura);
+        assert_eq!(Aura::new(Statistic::Health, AuraTarget::Allies, 0.4).stacking, Stacking::Always);
+    }
+
+    #[test]
+    fn test_aura_deserialize_rejects_unknown_statistic() {
+        let yaml: &str = "statistic: \"crit chance\"\ntarget: \"allies\"\nvalue: 0.2";
+
+        let aura: Result<Aura, serde_yaml::Error> = serde_yaml::from_str(yaml);
+
+        assert!(aura.is_err());
+    }
+
+    #[test]
+    fn test_aura_deserialize_rejects_unknown_field() {
+        let yaml: &str = "statistic: \"health\"\ntarget: \"allies\"\nvalue: 0.4\nid: \"tank health\"\nstackng: \"unique\"";
+
+        let aura: Result<Aura, serde_yaml::Error> = serde_yaml::from_str(yaml);
+
+        assert!(aura.is_err());
+    }
+
+    #[test]
+    fn test_ability_deserialize_rejects_unknown_target() {
+        let yaml: &str = "statistic: \"power\"\ntarget: \"everyone\"\nvalue: 0.1";
+
+        let effect: Result<AbilityEffect, serde_yaml::Error> = serde_yaml::from_str(yaml);
+
+        assert!(effect.is_err());
+    }
+
+    #[test]
+    fn test_ability_deserialize_rejects_unknown_field() {
+        let yaml: &str = "statstic: \"power\"\ntarget: \"allies\"\nvalue: 0.1";
+
+        let effect: Result<AbilityEffect, serde_yaml::Error> = serde_yaml::from_str(yaml);
+
+        assert!(effect.is_err());
+    }
+
+    #[test]
+    fn test_ability_deserialize_composite() {
+        let yaml: &str = "name: \"War Cry\"\ndescription: \"Power gains allies 10% and slows enemies by 1.\"\n\
+            trigger_event: \"battle_start\"\neffects:\n  - statistic: \"power\"\n    target: \"allies\"\n    value: 0.1\n\
+            \x20 - statistic: \"speed\"\n    target: \"enemies\"\n    value: -1";
+        let expected_ability = Ability::new_composite("War Cry", "Power gains allies 10% and slows enemies by 1.",
+            "battle_start", vec![AbilityEffect::new(Statistic::Power, AbilityTarget::Allies, 0.1),
+            AbilityEffect::new(Statistic::Speed, AbilityTarget::Enemies, -1.0)]);
+
+        let test_ability: Ability = serde_yaml::from_str(yaml).unwrap();
 
-        assert_eq!(new_value, EXPECTED_VALUE);
+        assert_eq!(test_ability, expected_ability);
     }
 }
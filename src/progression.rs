@@ -0,0 +1,180 @@
+/*
+Progression: experience and levelling. Characters earn experience from the outcome of a battle
+(kills, survival, and the levels of the enemies they faced) and level up along a configurable
+curve until the level cap. Each level up adds the per-class statistic growth read from data.
+*/
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::character::Character;
+use crate::configs;
+use crate::error;
+use crate::error::GameResult;
+
+// Base statistics gained on each level up
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[serde(deny_unknown_fields)]
+pub struct StatGrowth {
+    pub speed: i32,
+    pub health_max: i32,
+    pub power: i32,
+    pub critical_chance: f64,
+    pub dt: i32,
+    pub dr: f64
+}
+
+// Statistic growth keyed by class name
+pub type GrowthTables = HashMap<String, StatGrowth>;
+
+pub fn growth_tables_from_file(filepath: &str) -> GameResult<GrowthTables> {
+    error::read_yaml_file(filepath)
+}
+
+// Experience needed to go from a level to the next one
+pub fn xp_to_next_level(level: u32) -> i32 {
+    let exponent: i32 = level.saturating_sub(1) as i32;
+    (configs::XP_CURVE_BASE as f64 * configs::XP_CURVE_GROWTH.powi(exponent)).round() as i32
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// How a battle went for one squad (kills and survivors are listed in team order: squad positions,
+// then any characters replaced by reserves)
+#[derive(Clone, Debug, PartialEq)]
+pub struct BattleOutcome {
+    pub victory: bool,
+    pub enemy_levels: Vec<u32>,
+    pub kills: Vec<u32>,
+    pub survivors: Vec<bool>
+}
+
+impl BattleOutcome {
+    // Fallen characters are the ones replaced by reserves. Enemy reserves count towards the enemy
+    // levels, whether or not they entered the battle.
+    pub fn from_teams(team: &[Character], fallen: &[Character], enemy_team: &[Character], enemy_fallen: &[Character],
+        enemy_reserves: &[Character]) -> BattleOutcome {
+        BattleOutcome {
+            victory: team.iter().any(|character| !character.is_dead()),
+            enemy_levels: enemy_team.iter().chain(enemy_fallen.iter()).chain(enemy_reserves.iter())
+                .map(|character| character.level).collect(),
+            kills: team.iter().chain(fallen.iter()).map(|character| character.kills).collect(),
+            survivors: team.iter().chain(fallen.iter()).map(|character| !character.is_dead()).collect()
+        }
+    }
+
+    // Experience earned by the character at a position in the team
+    pub fn xp_for(&self, position: usize) -> i32 {
+        let mut xp: i32 = self.kills.get(position).copied().unwrap_or(0) as i32 * configs::XP_PER_KILL;
+        if self.survivors.get(position).copied().unwrap_or(false) {
+            xp += configs::XP_FOR_SURVIVING;
+        }
+        if self.victory {
+            xp += self.enemy_levels.iter().sum::<u32>() as i32 * configs::XP_PER_ENEMY_LEVEL;
+        }
+        xp
+    }
+}
+
+// Hand out experience for a battle, to the squad and to the characters replaced by reserves; returns
+// the levels gained by each of them (in outcome order)
+pub fn award_xp(team: &mut [Character], fallen: &mut [Character], outcome: &BattleOutcome,
+    growth_tables: &GrowthTables) -> Vec<u32> {
+    let mut levels_gained: Vec<u32> = Vec::with_capacity(team.len() + fallen.len());
+    for (position, character) in team.iter_mut().chain(fallen.iter_mut()).enumerate() {
+        let growth: StatGrowth = growth_tables.get(&character.class).cloned().unwrap_or_default();
+        levels_gained.push(character.add_xp(outcome.xp_for(position), &growth));
+    }
+    levels_gained
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::test_squad;
+
+    const GROWTH_TABLES: &str = "data/growth/class_growth.yml";
+
+    #[test]
+    fn test_xp_to_next_level() {
+        assert_eq!(xp_to_next_level(1), configs::XP_CURVE_BASE);
+        assert!(xp_to_next_level(2) > xp_to_next_level(1));
+        assert!(xp_to_next_level(10) > xp_to_next_level(9));
+    }
+
+    #[test]
+    fn test_battle_outcome_from_teams() {
+        let mut team: Vec<Character> = test_squad();
+        team[configs::FRONT_LEFT].kills = 2;
+        team[configs::BACK_LEFT].health = 0;
+        let mut enemy_team: Vec<Character> = test_squad();
+        for enemy in enemy_team.iter_mut() {
+            enemy.health = 0;
+            enemy.level = 2;
+        }
+        let mut fallen: Vec<Character> = test_squad()[..1].to_vec();
+        fallen[0].kills = 1;
+        fallen[0].health = 0;
+        let mut enemy_fallen: Vec<Character> = test_squad()[..1].to_vec();
+        enemy_fallen[0].level = 3;
+        let expected_outcome = BattleOutcome {victory: true, enemy_levels: vec![2, 2, 2, 2, 3, 1],
+            kills: vec![2, 0, 0, 0, 1], survivors: vec![true, true, false, true, false]};
+
+        let outcome = BattleOutcome::from_teams(&team, &fallen, &enemy_team, &enemy_fallen, &test_squad()[..1]);
+
+        assert_eq!(outcome, expected_outcome);
+    }
+
+    #[test]
+    fn test_battle_outcome_xp_for() {
+        let outcome = BattleOutcome {victory: true, enemy_levels: vec![1, 2], kills: vec![2, 0],
+            survivors: vec![true, false]};
+        let expected_xp: Vec<i32> = vec![
+            2 * configs::XP_PER_KILL + configs::XP_FOR_SURVIVING + 3 * configs::XP_PER_ENEMY_LEVEL,
+            3 * configs::XP_PER_ENEMY_LEVEL];
+
+        let xp: Vec<i32> = (0..2).map(|position| outcome.xp_for(position)).collect();
+
+        assert_eq!(xp, expected_xp);
+    }
+
+    #[test]
+    fn test_battle_outcome_xp_for_defeat() {
+        let outcome = BattleOutcome {victory: false, enemy_levels: vec![5], kills: vec![1], survivors: vec![false]};
+
+        assert_eq!(outcome.xp_for(0), configs::XP_PER_KILL);
+    }
+
+    #[test]
+    fn test_growth_tables_from_file() {
+        let expected_growth = StatGrowth {speed: 0, health_max: 4000, power: 400, critical_chance: 0.0, dt: 5, dr: 0.0};
+
+        let growth_tables: GrowthTables = growth_tables_from_file(GROWTH_TABLES).unwrap();
+
+        assert_eq!(growth_tables.get("Warrior"), Some(&expected_growth));
+    }
+
+    #[test]
+    fn test_award_xp() {
+        let mut team: Vec<Character> = test_squad();
+        let mut growth_tables: GrowthTables = HashMap::new();
+        growth_tables.insert("Warrior".to_string(), StatGrowth {power: 10, ..StatGrowth::default()});
+        let mut fallen: Vec<Character> = test_squad()[..1].to_vec();
+        let outcome = BattleOutcome {victory: false, enemy_levels: Vec::new(), kills: vec![2, 0, 0, 0, 3],
+            survivors: vec![true, true, true, true, false]};
+        let expected_levels: Vec<u32> = vec![1, 0, 0, 0, 1]; // 120 and 150 experience clear the first level
+        const EXPECTED_POWER: i32 = 60;
+
+        let levels_gained: Vec<u32> = award_xp(&mut team, &mut fallen, &outcome, &growth_tables);
+
+        assert_eq!(levels_gained, expected_levels);
+        assert_eq!(fallen[0].level, 2);
+        assert_eq!(team[configs::FRONT_LEFT].level, 2);
+        assert_eq!(team[configs::FRONT_LEFT].power(), EXPECTED_POWER);
+        assert_eq!(team[configs::FRONT_RIGHT].power(), 50);
+    }
+}
@@ -0,0 +1,200 @@
+/*
+Recruitment: every character has a cost (in points) and a rarity tier, and squads are built under a
+point budget. This file also holds a rough combat rating, used to search for the strongest squad
+that fits a budget (e.g. to balance encounters, or to suggest a squad to the player).
+
+File structure:
+- Rarity (enum)
+- Squad cost and combat rating
+- Strongest squad search
+- Tests
+*/
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+
+use crate::character::Character;
+use crate::configs;
+use crate::error::GameError;
+use crate::error::GameResult;
+use crate::squad;
+use crate::squad::SquadConstructor;
+
+// Squad members (the pet position isn't recruited)
+const SQUAD_MEMBERS: usize = configs::BACK_RIGHT + 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary
+}
+
+impl fmt::Display for Rarity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name: &str = match self {
+            Rarity::Common => "common",
+            Rarity::Uncommon => "uncommon",
+            Rarity::Rare => "rare",
+            Rarity::Epic => "epic",
+            Rarity::Legendary => "legendary"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+pub fn squad_cost(characters: &[Character]) -> u32 {
+    characters.iter().map(|character| character.cost).sum()
+}
+
+// How much damage a character can soak up before dying (damage reduction stretches health further)
+fn effective_health(character: &Character) -> f64 {
+    character.health_max() as f64 / (1.0 - character.dr())
+}
+
+// A rough measure of strength: effective health times expected damage per round (counting every
+// target an attack can hit). It ignores auras and abilities, so treat it as a first guess.
+pub fn combat_rating(character: &Character) -> f64 {
+    let targets: f64 = match character.attack_type.as_str() {
+        "row" | "column" => 2.0,
+        "all" => SQUAD_MEMBERS as f64,
+        _ => 1.0
+    };
+    let critical_bonus: f64 = character.critical_chance() * (configs::CRITICAL_MULTIPLIER - 1) as f64;
+    let damage: f64 = character.power() as f64 * (1.0 + critical_bonus) * targets;
+    effective_health(character) * damage
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Pick the squad (the same character may be picked more than once) with the highest total combat
+// rating that costs at most `budget`. Returns indices into `candidates`, sturdiest first so they
+// end up in the front row, or None if no full squad fits the budget.
+pub fn strongest_squad(candidates: &[Character], budget: u32) -> Option<Vec<usize>> {
+    let ratings: Vec<f64> = candidates.iter().map(combat_rating).collect();
+    let mut best: Option<(f64, Vec<usize>)> = None;
+    let mut picks: Vec<usize> = Vec::with_capacity(SQUAD_MEMBERS);
+    search_squads(candidates, &ratings, budget, 0, &mut picks, &mut best);
+    best.map(|(_, mut picks)| {
+        picks.sort_by(|a, b| effective_health(&candidates[*b]).total_cmp(&effective_health(&candidates[*a])));
+        picks
+    })
+}
+
+// Try every combination of candidates (in index order, so each squad is only tried once)
+fn search_squads(candidates: &[Character], ratings: &[f64], budget: u32, first: usize, picks: &mut Vec<usize>,
+    best: &mut Option<(f64, Vec<usize>)>) {
+    if picks.len() == SQUAD_MEMBERS {
+        let rating: f64 = picks.iter().map(|index| ratings[*index]).sum();
+        if best.as_ref().is_none_or(|(best_rating, _)| rating > *best_rating) {
+            *best = Some((rating, picks.clone()));
+        }
+        return;
+    }
+    let spent: u32 = picks.iter().map(|index| candidates[*index].cost).sum();
+    for index in first..candidates.len() {
+        if spent + candidates[index].cost <= budget {
+            picks.push(index);
+            search_squads(candidates, ratings, budget, index, picks, best);
+            picks.pop();
+        }
+    }
+}
+
+// The strongest squad under budget from every character file in a folder (with their equipment)
+pub fn strongest_squad_from_directory(directory_characters: &str, budget: u32) -> GameResult<Option<SquadConstructor>> {
+    let entries = fs::read_dir(directory_characters).map_err(|error| GameError::from_io(directory_characters, error))?;
+    let mut names: Vec<String> = Vec::new();
+    for entry in entries {
+        let path = entry.map_err(|error| GameError::from_io(directory_characters, error))?.path();
+        if path.extension().is_some_and(|extension| extension == "yml") {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                names.push(name.to_string());
+            }
+        }
+    }
+    names.sort();
+    let candidates: Vec<Character> = squad::characters_from_names(&names, directory_characters)?;
+    Ok(strongest_squad(&candidates, budget).map(|picks| {
+        let mut squad = SquadConstructor::new(picks.iter().map(|index| names[*index].clone()).collect());
+        squad.budget = Some(budget);
+        squad
+    }))
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recruit(name: &str, health: i32, power: i32, cost: u32) -> Character {
+        Character::builder(name).health(health).power(power).cost(cost).build()
+    }
+
+    #[test]
+    fn test_squad_cost() {
+        let squad: Vec<Character> = vec![recruit("A", 100, 10, 30), recruit("B", 100, 10, 20)];
+        const EXPECTED_COST: u32 = 50;
+
+        assert_eq!(squad_cost(&squad), EXPECTED_COST);
+    }
+
+    #[test]
+    fn test_combat_rating() {
+        let mut character: Character = Character::builder("Dave").health(100).power(10).dr(0.5).build();
+        const EXPECTED_SINGLE_RATING: f64 = 2000.0;
+        const EXPECTED_ROW_RATING: f64 = 4000.0;
+
+        assert!((combat_rating(&character) - EXPECTED_SINGLE_RATING).abs() < 1e-9);
+        character.attack_type = "row".to_string();
+        assert!((combat_rating(&character) - EXPECTED_ROW_RATING).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_strongest_squad_within_budget() {
+        let candidates: Vec<Character> = vec![recruit("Cheap", 100, 10, 10), recruit("Tank", 1000, 10, 40),
+            recruit("Star", 100, 1000, 60)];
+        // Star + 3 Cheap (90 points) beats 2 Tanks + 2 Cheap (100 points)
+        let expected_picks: Vec<usize> = vec![0, 0, 0, 2];
+
+        let mut picks: Vec<usize> = strongest_squad(&candidates, 100).unwrap();
+        picks.sort();
+
+        assert_eq!(picks, expected_picks);
+    }
+
+    #[test]
+    fn test_strongest_squad_sturdiest_first() {
+        let candidates: Vec<Character> = vec![recruit("Glass", 20, 1000, 30), recruit("Tank", 1000, 10, 10)];
+        let expected_picks: Vec<usize> = vec![1, 1, 1, 0];
+
+        let picks: Vec<usize> = strongest_squad(&candidates, 70).unwrap();
+
+        assert_eq!(picks, expected_picks);
+    }
+
+    #[test]
+    fn test_strongest_squad_over_budget() {
+        let candidates: Vec<Character> = vec![recruit("Pricey", 100, 10, 30)];
+
+        assert_eq!(strongest_squad(&candidates, 100), None);
+    }
+
+    #[test]
+    fn test_strongest_squad_from_directory() {
+        let squad: SquadConstructor = strongest_squad_from_directory("data/characters/", 100).unwrap().unwrap();
+        let characters: Vec<Character> = squad::characters_from_names(&squad.members, "data/characters/").unwrap();
+
+        assert_eq!(squad.members.len(), SQUAD_MEMBERS);
+        assert!(squad_cost(&characters) <= 100);
+    }
+}
@@ -0,0 +1,316 @@
+/*
+The roster holds the units the player owns. Each unit is an instance of a character file (its
+template) with its own id, level, experience, equipment, and injuries, and the roster is saved to
+disk between battles. Squad files can list roster unit ids instead of character names (see
+SquadConstructor::roster).
+
+File structure:
+- Injury (struct) definition, impl, and Apply impl
+- Unit (struct) definition
+- Roster (struct) definition and impl
+- Tests
+*/
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::character;
+use crate::character::Character;
+use crate::configs;
+use crate::error;
+use crate::error::GameError;
+use crate::error::GameResult;
+use crate::items;
+use crate::modifiers::Apply;
+use crate::modifiers::ModifierMode;
+use crate::modifiers::Statistic;
+use crate::progression;
+use crate::progression::GrowthTables;
+use crate::progression::StatGrowth;
+
+const INJURY_SOURCE: &str = "injury";
+
+// A lasting penalty from falling in battle, which heals after a number of battles
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Injury {
+    pub statistic: Statistic,
+    pub value: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<ModifierMode>,
+    pub battles: u32 // battles left until it heals
+}
+
+impl Injury {
+    // The injury a unit takes when it dies in battle
+    pub fn from_death() -> Injury {
+        Injury {
+            statistic: Statistic::Health,
+            value: configs::INJURY_HEALTH_PENALTY,
+            mode: None,
+            battles: configs::INJURY_BATTLES
+        }
+    }
+}
+
+impl Apply for Injury {
+    fn value(&self) -> f64 {
+        self.value
+    }
+
+    fn declared_mode(&self) -> Option<ModifierMode> {
+        self.mode
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Unit {
+    pub id: String, // unique within the roster
+    pub template: String, // character file the unit is built from, e.g. "GoodTank"
+    #[serde(default = "default_level")]
+    pub level: u32,
+    #[serde(default)]
+    pub xp: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equipment: Option<Vec<String>>, // replaces the template's equipment (omit to keep it)
+    #[serde(default)]
+    pub injuries: Vec<Injury>
+}
+
+fn default_level() -> u32 {
+    1
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Roster {
+    #[serde(default)]
+    pub units: Vec<Unit>,
+    #[serde(skip)]
+    path: String // where the roster was loaded from (for error messages)
+}
+
+// Growth tables: data/characters/ => data/growth/class_growth.yml
+fn growth_tables_path(directory_characters: &str) -> String {
+    character::data_directory(Path::new(directory_characters), "growth").join("class_growth.yml")
+        .to_string_lossy().to_string()
+}
+
+impl Roster {
+    // Unit ids must be unique, since units are looked up by id
+    pub fn new_from_file(filepath: &str) -> GameResult<Self> {
+        let mut roster: Roster = error::read_yaml_file(filepath)?;
+        for (index, unit) in roster.units.iter().enumerate() {
+            if roster.units[..index].iter().any(|other| other.id == unit.id) {
+                return Err(GameError::validation(filepath, &format!("unit id \"{}\" is used more than once", unit.id)));
+            }
+        }
+        roster.path = filepath.to_string();
+        Ok(roster)
+    }
+
+    pub fn save_to_file(&self, filepath: &str) -> GameResult<()> {
+        error::write_yaml_file(filepath, self)
+    }
+
+    pub fn unit(&self, id: &str) -> Option<&Unit> {
+        self.units.iter().find(|unit| unit.id == id)
+    }
+
+    // Add a level 1 unit of a character template; returns its id (e.g. "GoodTank-2")
+    pub fn recruit(&mut self, template: &str) -> String {
+        let id: String = (1..)
+            .map(|number| format!("{}-{}", template, number))
+            .find(|id| self.unit(id).is_none())
+            .unwrap_or_default();
+        self.units.push(Unit {
+            id: id.clone(),
+            template: template.to_string(),
+            level: default_level(),
+            xp: 0,
+            equipment: None,
+            injuries: Vec::new()
+        });
+        id
+    }
+
+    // Build a unit's character: the template, with the unit's equipment, level, and injuries
+    pub fn character(&self, id: &str, directory_characters: &str, growth_tables: &GrowthTables) -> GameResult<Character> {
+        let unit: &Unit = self.unit(id)
+            .ok_or_else(|| GameError::validation(&self.path, &format!("there is no unit with id \"{}\"", id)))?;
+        let mut character: Character = Character::new_from_file(&format!("{}{}.yml", directory_characters, unit.template))?;
+        if let Some(equipment) = &unit.equipment {
+            character.equipment = equipment.clone();
+        }
+        items::equip_from_directory(&mut character, &items::items_directory(directory_characters))?;
+        let growth: StatGrowth = growth_tables.get(&character.class).cloned().unwrap_or_default();
+        character.restore_progress(unit.level, unit.xp, &growth);
+        for injury in unit.injuries.iter() {
+            character.apply_modifier(injury.statistic, injury, INJURY_SOURCE);
+        }
+        character.roster_id = Some(unit.id.clone());
+        Ok(character)
+    }
+
+    // Build the characters for a list of unit ids (levels use the growth tables next to the characters)
+    pub fn characters(&self, ids: &[String], directory_characters: &str) -> GameResult<Vec<Character>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let growth_tables: GrowthTables = progression::growth_tables_from_file(&growth_tables_path(directory_characters))?;
+        ids.iter().map(|id| self.character(id, directory_characters, &growth_tables)).collect()
+    }
+
+    // Write a battle's results back to the roster: units keep their new levels and experience, units
+    // that died are injured, and older injuries heal a little. Pass the team as it is after the
+    // battle, along with the characters replaced by reserves.
+    pub fn record_battle(&mut self, team: &[Character], fallen: &[Character]) {
+        for unit in self.units.iter_mut() {
+            for injury in unit.injuries.iter_mut() {
+                injury.battles = injury.battles.saturating_sub(1);
+            }
+            unit.injuries.retain(|injury| injury.battles > 0);
+        }
+        for character in team.iter().chain(fallen.iter()) {
+            let unit: Option<&mut Unit> = character.roster_id.as_ref()
+                .and_then(|id| self.units.iter_mut().find(|unit| &unit.id == id));
+            if let Some(unit) = unit {
+                unit.level = character.level;
+                unit.xp = character.xp();
+                if character.is_dead() {
+                    unit.injuries.push(Injury::from_death());
+                }
+            }
+        }
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHARACTER_FOLDER: &str = "data/characters/";
+    const ROSTER: &str = "data/roster/roster.yml";
+
+    fn test_growth_tables() -> GrowthTables {
+        progression::growth_tables_from_file(&growth_tables_path(CHARACTER_FOLDER)).unwrap()
+    }
+
+    #[test]
+    fn test_growth_tables_path() {
+        assert_eq!(growth_tables_path(CHARACTER_FOLDER), "data/growth/class_growth.yml");
+    }
+
+    #[test]
+    fn test_new_from_file() {
+        let roster: Roster = Roster::new_from_file(ROSTER).unwrap();
+
+        assert!(roster.unit("GoodTank-1").is_some());
+        assert!(roster.unit("GoodTank-9").is_none());
+    }
+
+    #[test]
+    fn test_new_from_file_duplicate_id() {
+        let path = std::env::temp_dir().join("test_roster_duplicate_id.yml");
+        let path: &str = &path.to_string_lossy();
+        std::fs::write(path, "units:\n  - id: \"GoodTank-1\"\n    template: \"GoodTank\"\n  - id: \"GoodTank-1\"\n    template: \"GoodTank\"\n").unwrap();
+
+        match Roster::new_from_file(path) {
+            Err(GameError::Validation {path: error_path, message}) => {
+                assert_eq!(error_path, path);
+                assert!(message.contains("GoodTank-1"));
+            },
+            other => panic!("expected a validation error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_recruit_unique_ids() {
+        let mut roster: Roster = Roster::default();
+
+        let first: String = roster.recruit("GoodTank");
+        let second: String = roster.recruit("GoodTank");
+
+        assert_eq!(first, "GoodTank-1");
+        assert_eq!(second, "GoodTank-2");
+    }
+
+    #[test]
+    fn test_character_applies_level_and_equipment() {
+        let mut roster: Roster = Roster::default();
+        let id: String = roster.recruit("GoodSwordsman");
+        roster.units[0].level = 3;
+        roster.units[0].xp = 40;
+        roster.units[0].equipment = Some(Vec::new());
+        let template: Character = Character::new_from_file("data/characters/GoodSwordsman.yml").unwrap();
+        let growth: StatGrowth = test_growth_tables()["Warrior"].clone();
+
+        let character: Character = roster.character(&id, CHARACTER_FOLDER, &test_growth_tables()).unwrap();
+
+        assert_eq!(character.level, 3);
+        assert_eq!(character.xp(), 40);
+        assert_eq!(character.power(), template.base.power + 2 * growth.power); // no IronSword
+        assert!(character.items.is_empty());
+        assert_eq!(character.roster_id, Some(id));
+    }
+
+    #[test]
+    fn test_character_applies_injuries() {
+        let mut roster: Roster = Roster::default();
+        let id: String = roster.recruit("GoodTank");
+        roster.units[0].injuries.push(Injury::from_death());
+        let healthy: Character = crate::squad::character_from_name("GoodTank", CHARACTER_FOLDER).unwrap();
+
+        let injured: Character = roster.character(&id, CHARACTER_FOLDER, &test_growth_tables()).unwrap();
+
+        assert!(injured.health_max() < healthy.health_max());
+        assert_eq!(injured.health, injured.health_max());
+    }
+
+    #[test]
+    fn test_character_unknown_id() {
+        let roster: Roster = Roster::new_from_file(ROSTER).unwrap();
+
+        match roster.character("Nobody-1", CHARACTER_FOLDER, &test_growth_tables()) {
+            Err(GameError::Validation {path, ..}) => assert_eq!(path, ROSTER),
+            other => panic!("expected a validation error, got {:?}", other.map(|character| character.name))
+        }
+    }
+
+    #[test]
+    fn test_record_battle() {
+        let mut roster: Roster = Roster::default();
+        let survivor_id: String = roster.recruit("GoodTank");
+        let fallen_id: String = roster.recruit("GoodSwordsman");
+        roster.units[0].injuries.push(Injury {battles: 1, ..Injury::from_death()});
+        let mut team: Vec<Character> = roster.characters(&[survivor_id, fallen_id], CHARACTER_FOLDER).unwrap();
+        team[0].add_xp(progression::xp_to_next_level(1), &StatGrowth::default());
+        team[1].health = 0;
+        let fallen: Vec<Character> = vec![team.remove(1)]; // replaced by a reserve
+
+        roster.record_battle(&team, &fallen);
+
+        assert_eq!(roster.units[0].level, 2);
+        assert!(roster.units[0].injuries.is_empty()); // healed
+        assert_eq!(roster.units[1].injuries, vec![Injury::from_death()]);
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let path = std::env::temp_dir().join("test_roster_save_round_trip.yml");
+        let path: &str = &path.to_string_lossy();
+        let mut roster: Roster = Roster::new_from_file(ROSTER).unwrap();
+        roster.recruit("EvilMage");
+
+        assert_eq!(roster.save_to_file(path), Ok(()));
+        assert_eq!(Roster::new_from_file(path).unwrap().units, roster.units);
+    }
+}
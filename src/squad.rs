@@ -1,56 +1,68 @@
 /*
 A squad is a vector of characters (order matters!). Most combat operations involve looping over a
-squad. This file holds the SquadConstructor struct, which consumes a vector of strings and returns
-a vector of Character (structs), applying relevant auras and whatnot.
+squad. This file holds the SquadConstructor struct, which consumes a vector of strings (character
+names, or roster unit ids) and returns a vector of Character (structs), applying formation bonuses
+and whatnot. Auras are applied once both squads are built. It also holds the functions that move
+characters between positions mid-battle.
 */
 
 use serde::Deserialize;
-use std::error::Error;
-use std::fs::File;
-use std::io::BufReader;
+use serde::Serialize;
 use std::path::Path;
+use std::path::PathBuf;
 
 use crate::character;
 use character::Character;
 use crate::configs;
+use crate::error;
+use crate::error::GameError;
+use crate::error::GameResult;
 use crate::input;
-use crate::modifiers::Apply;
+use crate::items;
 use crate::modifiers::Aura;
+use crate::modifiers::AuraTarget;
+use crate::modifiers::PositionModifier;
+use crate::recruitment;
+use crate::roster::Roster;
+use crate::targeting;
 
-#[derive(Deserialize)]
-pub struct SquadConstructor {
-    pub members: Vec<String>
-}
+// A pair of squad positions whose occupants traded places
+pub type Swap = (usize, usize);
 
-// Read squad members from file
-fn read_params_from_file<P: AsRef<Path>>(path: P) -> Result<SquadConstructor, Box<dyn Error>> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let params = serde_yaml::from_reader(reader)?;
-    Ok(params)
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SquadConstructor {
+    pub members: Vec<String>,
+    #[serde(default)]
+    pub reserves: Vec<String>, // benched characters who replace fallen members (in order)
+    #[serde(default)]
+    pub formation: Vec<PositionModifier>, // formation bonuses shared by every member of the squad
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget: Option<u32>, // most points the members and reserves may cost together (omit for no limit)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roster: Option<String> // roster file (relative to the squad file) whose unit ids the squad lists
 }
 
 impl SquadConstructor {
-    fn new(
+    pub fn new(
         members: Vec<String>
     ) -> SquadConstructor {
         SquadConstructor {
-            members: members
+            members: members,
+            reserves: Vec::new(),
+            formation: Vec::new(),
+            budget: None,
+            roster: None
         }
     }
 
-    pub fn new_from_file(filepath: &str) -> Self {
-        let squadc: SquadConstructor = read_params_from_file(filepath).unwrap();
-        return squadc;
+    pub fn new_from_file(filepath: &str) -> GameResult<Self> {
+        error::read_yaml_file(filepath)
     }
 
-    fn build_from_input() -> Self {
-        let front_left = input::grab_input(&"Choose character for the front-left position: ");
-        let front_right = input::grab_input(&"Choose character for the front-right position: ");
-        let back_left = input::grab_input(&"Choose character for the back-left position: ");
-        let back_right = input::grab_input(&"Choose character for the back-right position: ");
-        let squad_constructor: Vec<String> = vec![front_left, front_right, back_left, back_right];
-        return SquadConstructor::new(squad_constructor);
+    // Export the squad (e.g. to share it with another player)
+    pub fn save_to_file(&self, filepath: &str) -> GameResult<()> {
+        error::write_yaml_file(filepath, self)
     }
 
     #[allow(dead_code)]
@@ -61,97 +73,621 @@ impl SquadConstructor {
 
 /* --------------------------------------------------------------------------------------------- */
 
-fn apply_auras(mut squad: Vec<Character>) -> Vec<Character> {
-    // Loops twice: first over the squad to collect all auras and specific targets,
-    // and then again over the collected auras to apply them to the squad
-
-    // Note that auras that apply to self are applied in the first pass
-    let mut deferred_auras: Vec<Aura> = Vec::new();
-    for character in squad.iter_mut() {
-        for aura in character.auras.iter() {
-            if aura.target == "self".to_string() {
-                if aura.statistic == "health".to_string() {
-                    let new_health: i32 = aura.change_health(character.health_max, configs::MAXIMUM_HEALTH);
-                    character.health_max = new_health;
-                    character.health = new_health;
-                } else if aura.statistic == "power".to_string() {
-                    let new_power: i32 = aura.change_power(character.power);
-                    character.power = new_power;
-                } else if aura.statistic == "critical chance".to_string() {
-                    let new_crit: f64 = aura.change_crit_chance(character.critical_chance);
-                    character.critical_chance = new_crit;
-                } else if aura.statistic == "speed".to_string() {
-                    let new_speed: i32 = aura.change_speed(character.speed);
-                    character.speed = new_speed;
-                }
-            } else { // collect for deferred initialization
-                deferred_auras.push(aura.clone());
+// Apply the auras carried by one squad. Auras are evaluated once both squads are built, since some
+// of them (e.g. enemy debuffs) reach across to the opposing squad. Each character's incoming auras
+// are resolved against their stacking policies before being applied.
+pub fn apply_auras(allied_team: &mut [Character], enemy_team: &mut [Character]) {
+    let carried_auras: Vec<(usize, String, Aura)> = carried_auras(allied_team);
+    for (position, character) in allied_team.iter_mut().enumerate() {
+        receive_allied_auras(&carried_auras, position, character);
+    }
+    for character in enemy_team.iter_mut() {
+        receive_enemy_auras(&carried_auras, character);
+    }
+}
+
+// Give the characters at the given positions the auras they would have received at the start of
+// the battle, from their allies (themselves included) and from the enemy squad. The entrants' own
+// auras reach only themselves: the auras of the characters they replaced stay in effect instead.
+pub fn apply_entrant_auras(positions: &[usize], allied_team: &mut [Character], enemy_team: &[Character]) {
+    let allied_auras: Vec<(usize, String, Aura)> = carried_auras(allied_team);
+    let enemy_auras: Vec<(usize, String, Aura)> = carried_auras(enemy_team);
+    for position in positions.iter() {
+        receive_allied_auras(&allied_auras, *position, &mut allied_team[*position]);
+        receive_enemy_auras(&enemy_auras, &mut allied_team[*position]);
+    }
+}
+
+// Every aura carried in a squad, with the carrier's position and the source of its modifiers
+fn carried_auras(team: &[Character]) -> Vec<(usize, String, Aura)> {
+    team.iter().enumerate()
+        .flat_map(|(position, character)| character.auras.iter()
+            .map(move |aura| (position, aura_source(character), aura.clone())))
+        .collect()
+}
+
+fn receive_allied_auras(carried_auras: &[(usize, String, Aura)], position: usize, character: &mut Character) {
+    let incoming: Vec<&(usize, String, Aura)> = carried_auras.iter()
+        .filter(|(carrier_position, _, aura)| aura_reaches(&aura.target, *carrier_position, position, character))
+        .collect();
+    for (_, source, aura) in resolve_stacking(incoming) {
+        character.apply_modifier(aura.statistic, aura, source);
+    }
+}
+
+fn receive_enemy_auras(carried_auras: &[(usize, String, Aura)], character: &mut Character) {
+    let incoming: Vec<&(usize, String, Aura)> = carried_auras.iter()
+        .filter(|(_, _, aura)| aura.target == AuraTarget::Enemies)
+        .collect();
+    for (_, source, aura) in resolve_stacking(incoming) {
+        character.apply_modifier(aura.statistic, aura, source);
+    }
+}
+
+// Keep the auras a character actually receives. Auras without an id always apply; auras sharing an
+// id follow the stacking policy of the first of them (in squad order), keeping the strongest values
+// (largest in size, so a debuff keeps its harshest copies). Ties go to the carrier earlier in the
+// squad, so the outcome doesn't depend on anything but order.
+fn resolve_stacking(incoming: Vec<&(usize, String, Aura)>) -> Vec<&(usize, String, Aura)> {
+    let mut kept: Vec<&(usize, String, Aura)> = Vec::with_capacity(incoming.len());
+    let mut resolved_ids: Vec<&str> = Vec::new();
+    for entry in incoming.iter() {
+        let id: &str = match &entry.2.id {
+            Some(id) => id,
+            None => {
+                kept.push(entry);
+                continue;
             }
+        };
+        if resolved_ids.contains(&id) {
+            continue;
         }
+        resolved_ids.push(id);
+        let mut group: Vec<&(usize, String, Aura)> = incoming.iter()
+            .filter(|other| other.2.id.as_deref() == Some(id))
+            .cloned()
+            .collect();
+        group.sort_by(|a, b| b.2.value.abs().partial_cmp(&a.2.value.abs()).unwrap_or(std::cmp::Ordering::Equal));
+        let limit: usize = entry.2.stacking.limit().unwrap_or(group.len());
+        kept.extend(group.into_iter().take(limit));
     }
-    // Loop across deferred auras
-    for aura in deferred_auras {
-        if aura.target == "allies".to_string() { // handle auras for entire party
-            for character in squad.iter_mut() {
-                if aura.statistic == "health".to_string() {
-                    let new_health: i32 = aura.change_health(character.health_max, configs::MAXIMUM_HEALTH);
-                    character.health_max = new_health;
-                    character.health = new_health;
-                } else if aura.statistic == "power".to_string() {
-                    let new_power: i32 = aura.change_power(character.power);
-                    character.power = new_power;
-                } else if aura.statistic == "critical chance".to_string() {
-                    let new_crit: f64 = aura.change_crit_chance(character.critical_chance);
-                    character.critical_chance = new_crit;
-                } else if aura.statistic == "speed".to_string() {
-                    let new_speed: i32 = aura.change_speed(character.speed);
-                    character.speed = new_speed;
-                }
-            }
+    kept
+}
+
+// Apply the auras of both squads (heroes first, then villains)
+pub fn apply_battle_auras(hero_team: &mut [Character], villain_team: &mut [Character]) {
+    apply_auras(hero_team, villain_team);
+    apply_auras(villain_team, hero_team);
+}
+
+// Whether an aura carried at one position reaches the ally at another position
+fn aura_reaches(target: &AuraTarget, carrier_position: usize, position: usize, character: &Character) -> bool {
+    match target {
+        AuraTarget::Itself => carrier_position == position,
+        AuraTarget::Allies => true,
+        AuraTarget::AlliesWithTag(tag) => character.tags.contains(tag),
+        AuraTarget::AlliesOfClass(class) => character.class == *class,
+        AuraTarget::FrontRow => targeting::is_front_row(position),
+        AuraTarget::Adjacent => targeting::is_adjacent(carrier_position, position),
+        AuraTarget::Enemies => false
+    }
+}
+
+// Modifiers granted by auras are labelled with the character that carries the aura
+fn aura_source(character: &Character) -> String {
+    format!("aura: {}", character.name)
+}
+
+// Hand the squad-wide formation bonuses to each member, then apply bonuses for current positions.
+// Characters keep their position modifiers, so bonuses can be recomputed when they move.
+fn apply_formation(mut squad: Vec<Character>, squad_formation: &[PositionModifier]) -> Vec<Character> {
+    for (position, character) in squad.iter_mut().enumerate() {
+        character.squad_formation = squad_formation.to_vec();
+        character.apply_formation(position);
+    }
+    squad
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Trade the occupants of two positions and recompute their position modifiers
+pub fn swap_positions(squad: &mut [Character], position_a: usize, position_b: usize) -> Swap {
+    squad.swap(position_a, position_b);
+    squad[position_a].apply_formation(position_a);
+    squad[position_b].apply_formation(position_b);
+    (position_a, position_b)
+}
+
+// Once the whole front row is dead, the back row steps up (columns are preserved)
+pub fn advance_back_row(squad: &mut [Character]) -> Vec<Swap> {
+    let mut swaps: Vec<Swap> = Vec::new();
+    let front_row_alive = squad.iter().enumerate()
+        .any(|(position, character)| targeting::is_front_row(position) && !character.is_dead());
+    if front_row_alive {
+        return swaps;
+    }
+    for (front, back) in [(configs::FRONT_LEFT, configs::BACK_LEFT), (configs::FRONT_RIGHT, configs::BACK_RIGHT)].iter() {
+        if *back < squad.len() && !squad[*back].is_dead() {
+            squad[*back].print_moved("front row");
+            swaps.push(swap_positions(squad, *front, *back));
         }
     }
+    swaps
+}
+
+// Swap a back-row character with the front-row position in the same column
+pub fn push_to_front(squad: &mut [Character], position: usize) -> Option<Swap> {
+    let destination: usize = match position {
+        configs::BACK_LEFT => configs::FRONT_LEFT,
+        configs::BACK_RIGHT => configs::FRONT_RIGHT,
+        _ => return None
+    };
+    if position >= squad.len() || squad[position].is_dead() {
+        return None;
+    }
+    squad[position].print_moved("front row");
+    Some(swap_positions(squad, position, destination))
+}
 
-    return squad;
+// Swap a front-row character with the back-row position in the same column
+pub fn retreat_to_back(squad: &mut [Character], position: usize) -> Option<Swap> {
+    let destination: usize = match position {
+        configs::FRONT_LEFT => configs::BACK_LEFT,
+        configs::FRONT_RIGHT => configs::BACK_RIGHT,
+        _ => return None
+    };
+    if destination >= squad.len() || squad[position].is_dead() {
+        return None;
+    }
+    squad[position].print_moved("back row");
+    Some(swap_positions(squad, position, destination))
+}
+
+// Fill the positions of fallen characters with the next reserves in line. Entrants get their
+// position modifiers and battle auras on arrival, and the characters they replace are moved to
+// `fallen`; returns the positions that were filled.
+pub fn substitute_reserves(squad: &mut [Character], reserves: &mut Vec<Character>, enemy_team: &[Character],
+    fallen: &mut Vec<Character>) -> Vec<usize> {
+    let mut filled_positions: Vec<usize> = Vec::new();
+    for (position, slot) in squad.iter_mut().enumerate() {
+        if reserves.is_empty() {
+            break;
+        }
+        if slot.is_dead() {
+            let mut entrant: Character = reserves.remove(0);
+            entrant.apply_formation(position);
+            entrant.print_entered();
+            fallen.push(std::mem::replace(slot, entrant));
+            filled_positions.push(position);
+        }
+    }
+    apply_entrant_auras(&filled_positions, squad, enemy_team);
+    filled_positions
 }
 
 /* --------------------------------------------------------------------------------------------- */
 
-pub fn squad_from_file(filepath: String, directory_characters: &str) -> Vec<Character> {
-    let squad_member_names = SquadConstructor::new_from_file(&filepath);
-    let mut squad = Vec::with_capacity(5);
-    for character_string in squad_member_names.members.iter() {
-        let character_path: &str = &format!("{}{}.yml", directory_characters, &character_string);
-        squad.push(Character::new_from_file(character_path));
+// Load a character and equip their items (so item bonuses are in place before auras are applied)
+pub fn character_from_name(name: &str, directory_characters: &str) -> GameResult<Character> {
+    let character_path: &str = &format!("{}{}.yml", directory_characters, name);
+    let mut character: Character = Character::new_from_file(character_path)?;
+    items::equip_from_directory(&mut character, &items::items_directory(directory_characters))?;
+    Ok(character)
+}
+
+pub fn characters_from_names(names: &[String], directory_characters: &str) -> GameResult<Vec<Character>> {
+    names.iter().map(|name| character_from_name(name, directory_characters)).collect()
+}
+
+pub fn squad_from_file(filepath: String, directory_characters: &str) -> GameResult<Vec<Character>> {
+    let (squad, _reserves) = squad_with_reserves_from_file(filepath, directory_characters)?;
+    Ok(squad)
+}
+
+// Squads are returned without auras (see apply_battle_auras). Reserves inherit squad formation bonuses.
+// Squads that name a roster list roster unit ids, everything else lists character names.
+pub fn squad_with_reserves_from_file(filepath: String, directory_characters: &str) -> GameResult<(Vec<Character>, Vec<Character>)> {
+    let squad_member_names = SquadConstructor::new_from_file(&filepath)?;
+    let (squad, mut reserves) = match &squad_member_names.roster {
+        Some(roster_file) => {
+            let roster_path: PathBuf = Path::new(&filepath).parent().unwrap_or_else(|| Path::new("")).join(roster_file);
+            let roster: Roster = Roster::new_from_file(&roster_path.to_string_lossy())?;
+            (roster.characters(&squad_member_names.members, directory_characters)?,
+                roster.characters(&squad_member_names.reserves, directory_characters)?)
+        },
+        None => (characters_from_names(&squad_member_names.members, directory_characters)?,
+            characters_from_names(&squad_member_names.reserves, directory_characters)?)
+    };
+    if let Some(budget) = squad_member_names.budget {
+        check_budget(&filepath, budget, &[squad.as_slice(), reserves.as_slice()].concat())?;
+    }
+    let squad_output = apply_formation(squad, &squad_member_names.formation);
+    for reserve in reserves.iter_mut() {
+        reserve.squad_formation = squad_member_names.formation.clone();
+    }
+    Ok((squad_output, reserves))
+}
+
+// Recruiting a squad costs the sum of its characters' costs (reserves included)
+fn check_budget(filepath: &str, budget: u32, recruits: &[Character]) -> GameResult<()> {
+    let cost: u32 = recruitment::squad_cost(recruits);
+    if cost > budget {
+        return Err(GameError::validation(filepath, &format!("squad costs {} points, over its budget of {}", cost,
+            budget)));
+    }
+    Ok(())
+}
+
+// Keep asking until the player names a character that loads
+fn character_from_input(prompt: &str, directory_characters: &str) -> Character {
+    loop {
+        let name: String = input::grab_input(prompt);
+        match character_from_name(&name, directory_characters) {
+            Ok(character) => return character,
+            Err(GameError::MissingFile {..}) => println!("There is no character called \"{}\", please try again.", name),
+            Err(error) => println!("Could not load {}: {}. Please choose another character.", name, error)
+        }
     }
-    let squad_output = apply_auras(squad);
-    return squad_output;
 }
 
 pub fn squad_from_input(directory_characters: &str) -> Vec<Character> {
-    let squad_member_names = SquadConstructor::build_from_input();
-    let mut squad = Vec::with_capacity(5);
-    for character_string in squad_member_names.members.iter() {
-        let character_path: &str = &format!("{}{}.yml", directory_characters, &character_string);
-        squad.push(Character::new_from_file(character_path));
+    let prompts: [&str; 4] = ["Choose character for the front-left position: ",
+        "Choose character for the front-right position: ", "Choose character for the back-left position: ",
+        "Choose character for the back-right position: "];
+    loop {
+        let squad: Vec<Character> = prompts.iter()
+            .map(|prompt| character_from_input(prompt, directory_characters))
+            .collect();
+        let cost: u32 = recruitment::squad_cost(&squad);
+        if cost <= configs::SQUAD_BUDGET {
+            return apply_formation(squad, &[]);
+        }
+        println!("That squad costs {} points, but you only have {}. Please choose again.", cost, configs::SQUAD_BUDGET);
     }
-    let squad_output = apply_auras(squad);
-    return squad_output;
 }
 
 /* --------------------------------------------------------------------------------------------- */
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
-//
-//     // Aura struct tests
-//     #[test]
-//     fn test_squad_apply_auras() {
-//         let mut test_squad: Vec<Character> = squad::squad_from_file(hero_filepath, &character_folder);
-//
-//
-//
-//         assert_eq!(new_value, EXPECTED_VALUE);
-//
-//     }
-// }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modifiers::Placement;
+    use crate::modifiers::Stacking;
+    use crate::modifiers::Statistic;
+    use crate::tags::Tag;
+    use crate::test_helpers::test_squad;
+
+    const CHARACTER_FOLDER: &str = "data/characters/";
+    const HERO_SQUAD: &str = "data/squad/test_hero_squad.yml";
+    const VILLAIN_SQUAD: &str = "data/squad/test_villain_squad.yml";
+    const ROSTER_SQUAD: &str = "data/squad/test_roster_squad.yml";
+
+    #[test]
+    fn test_squad_from_file() {
+        let expected_names: Vec<&str> = vec!["The Good Tank", "The Good Swordsman", "The Good Rogue",
+            "The Good Archer"];
+
+        let squad: Vec<Character> = squad_from_file(HERO_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+        let names: Vec<&str> = squad.iter().map(|character| character.name.as_str()).collect();
+
+        assert_eq!(names, expected_names);
+    }
+
+    #[test]
+    fn test_villain_squad_from_file() {
+        let expected_names: Vec<&str> = vec!["The Dragon", "The Evil Tank", "The Evil Swordsman",
+            "The Evil Mage"];
+
+        let squad: Vec<Character> = squad_from_file(VILLAIN_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+        let names: Vec<&str> = squad.iter().map(|character| character.name.as_str()).collect();
+
+        assert_eq!(names, expected_names);
+    }
+
+    #[test]
+    fn test_squad_constructor_save_round_trip() {
+        let path = std::env::temp_dir().join("test_squad_constructor_save_round_trip.yml");
+        let path: &str = &path.to_string_lossy();
+        let squad: SquadConstructor = SquadConstructor::new_from_file(HERO_SQUAD).unwrap();
+
+        assert_eq!(squad.save_to_file(path), Ok(()));
+        assert_eq!(SquadConstructor::new_from_file(path), Ok(squad));
+    }
+
+    #[test]
+    fn test_squad_from_roster_file() {
+        let expected_ids: Vec<Option<String>> = ["GoodTank-1", "GoodSwordsman-1", "GoodMeleeRogue-1", "GoodArcherRogue-1"]
+            .iter().map(|id| Some(id.to_string())).collect();
+        const EXPECTED_TANK_LEVEL: u32 = 3;
+
+        let squad: Vec<Character> = squad_from_file(ROSTER_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+        let ids: Vec<Option<String>> = squad.iter().map(|character| character.roster_id.clone()).collect();
+
+        assert_eq!(ids, expected_ids);
+        assert_eq!(squad[configs::FRONT_LEFT].level, EXPECTED_TANK_LEVEL);
+    }
+
+    #[test]
+    fn test_squad_over_budget() {
+        let path = std::env::temp_dir().join("test_squad_over_budget.yml");
+        let path: String = path.to_string_lossy().to_string();
+        let mut squad: SquadConstructor = SquadConstructor::new_from_file(HERO_SQUAD).unwrap();
+        squad.budget = Some(100);
+        squad.save_to_file(&path).unwrap();
+
+        match squad_from_file(path.clone(), CHARACTER_FOLDER) {
+            Err(GameError::Validation {message, ..}) => assert_eq!(message, "squad costs 120 points, over its budget of 100"),
+            other => panic!("expected a budget error, got {:?}", other.map(|squad| squad.len()))
+        }
+    }
+
+    #[test]
+    fn test_squad_from_file_missing() {
+        let result = squad_from_file("data/squad/nobody.yml".to_string(), CHARACTER_FOLDER);
+
+        assert_eq!(result, Err(GameError::MissingFile {path: "data/squad/nobody.yml".to_string()}));
+    }
+
+    #[test]
+    fn test_character_from_name_unknown() {
+        let result = character_from_name("Nobody", CHARACTER_FOLDER);
+
+        assert_eq!(result, Err(GameError::MissingFile {path: "data/characters/Nobody.yml".to_string()}));
+    }
+
+    #[test]
+    fn test_squad_with_reserves_from_file() {
+        const EXPECTED_RESERVE: &str = "The Good Swordsman";
+        const EXPECTED_RESERVE_HEALTH: i32 = 45000; // auras wait until the reserve enters
+
+        let (squad, reserves) = squad_with_reserves_from_file(HERO_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+
+        assert_eq!(squad.len(), 4);
+        assert_eq!(reserves.len(), 1);
+        assert_eq!(reserves[0].name, EXPECTED_RESERVE);
+        assert_eq!(reserves[0].health, EXPECTED_RESERVE_HEALTH);
+    }
+
+    #[test]
+    fn test_save_squad_member() {
+        let path = std::env::temp_dir().join("test_save_squad_member.yml");
+        let path: &str = &path.to_string_lossy();
+        let (mut squad, _) = squad_with_reserves_from_file(HERO_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+        let (mut villains, _) = squad_with_reserves_from_file(VILLAIN_SQUAD.to_string(), CHARACTER_FOLDER).unwrap();
+        apply_battle_auras(&mut squad, &mut villains);
+        let template: Character = Character::new_from_file("data/characters/GoodTank.yml").unwrap();
+
+        assert_eq!(squad[configs::FRONT_LEFT].save_to_file(path), Ok(()));
+        let loaded: Character = character::read_params_from_file(path).unwrap(); // as saved, before any fixes
+
+        assert_eq!(loaded.formation, template.formation);
+        assert_eq!(loaded.health, template.health);
+        assert!(crate::validation::validate_character(&loaded, path).issues.is_empty());
+    }
+
+    #[test]
+    fn test_squad_apply_formation() {
+        let squad: Vec<Character> = vec![
+            Character::builder("Dave").class("Warrior").speed(0).power(50).critical_chance(0.1).build(),
+            Character::builder("Logan").class("Rogue").speed(0).power(50).critical_chance(0.1).build(),
+            Character::builder("William").class("Archer").speed(0).power(50).critical_chance(0.1).build()];
+        let formation: Vec<PositionModifier> = vec![PositionModifier::new(Statistic::Dr, Placement::FrontRow, 0.2)];
+        let expected_dr: Vec<f64> = vec![0.2, 0.2, 0.0];
+
+        let squad: Vec<Character> = apply_formation(squad, &formation);
+        let dr: Vec<f64> = squad.iter().map(|character| character.dr()).collect();
+
+        assert_eq!(dr, expected_dr);
+    }
+
+    // Aura tests
+    fn aura_test_squad(aura: Aura) -> Vec<Character> {
+        vec![
+            Character::builder("Tank").class("Warrior").speed(0).power(100).critical_chance(0.1)
+                .auras(vec![aura]).build(),
+            Character::builder("Ghoul").class("Warrior").speed(0).power(100).critical_chance(0.1)
+                .tags(vec![Tag::Undead]).build(),
+            Character::builder("Mage").class("Mage").speed(0).power(100).critical_chance(0.1).build(),
+            Character::builder("Archer").class("Archer").speed(0).power(100).critical_chance(0.1).build()]
+    }
+
+    fn aura_powers(aura: Aura) -> (Vec<i32>, Vec<i32>) {
+        let mut allied_team: Vec<Character> = aura_test_squad(aura);
+        let mut enemy_team: Vec<Character> = test_squad();
+        apply_auras(&mut allied_team, &mut enemy_team);
+        (allied_team.iter().map(|character| character.power()).collect(),
+            enemy_team.iter().map(|character| character.power()).collect())
+    }
+
+    #[test]
+    fn test_apply_auras_allies_with_tag() {
+        let expected_powers: Vec<i32> = vec![100, 150, 100, 100];
+
+        let (powers, _) = aura_powers(Aura::new(Statistic::Power, AuraTarget::AlliesWithTag(Tag::Undead), 0.5));
+
+        assert_eq!(powers, expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_allies_of_class() {
+        let expected_powers: Vec<i32> = vec![100, 100, 150, 100];
+
+        let (powers, _) = aura_powers(Aura::new(Statistic::Power, AuraTarget::AlliesOfClass("Mage".to_string()), 0.5));
+
+        assert_eq!(powers, expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_front_row() {
+        let expected_powers: Vec<i32> = vec![150, 150, 100, 100];
+
+        let (powers, _) = aura_powers(Aura::new(Statistic::Power, AuraTarget::FrontRow, 0.5));
+
+        assert_eq!(powers, expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_adjacent() {
+        let expected_powers: Vec<i32> = vec![100, 150, 150, 100];
+
+        let (powers, _) = aura_powers(Aura::new(Statistic::Power, AuraTarget::Adjacent, 0.5));
+
+        assert_eq!(powers, expected_powers);
+    }
+
+    fn stacking_powers(stacking: Stacking) -> Vec<i32> {
+        let mut allied_team: Vec<Character> = test_squad();
+        for (position, value) in [(configs::FRONT_LEFT, 0.2), (configs::FRONT_RIGHT, 0.6), (configs::BACK_LEFT, 0.4)].iter() {
+            allied_team[*position].auras = vec![Aura::new_unique(Statistic::Power, AuraTarget::Allies, *value,
+                "battle cry", stacking)];
+        }
+        apply_auras(&mut allied_team, &mut Vec::new());
+        allied_team.iter().map(|character| character.power()).collect()
+    }
+
+    #[test]
+    fn test_apply_auras_stacking_unique() {
+        let expected_powers: Vec<i32> = vec![80; 4]; // only the +60% aura applies
+
+        assert_eq!(stacking_powers(Stacking::Unique), expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_stacking_unique_debuff() {
+        let mut allied_team: Vec<Character> = test_squad();
+        let mut enemy_team: Vec<Character> = test_squad();
+        for (position, value) in [(configs::FRONT_LEFT, -0.2), (configs::FRONT_RIGHT, -0.6)].iter() {
+            allied_team[*position].auras = vec![Aura::new_unique(Statistic::Power, AuraTarget::Enemies, *value,
+                "war cry", Stacking::Unique)];
+        }
+        let expected_enemy_powers: Vec<i32> = vec![20; 4]; // only the -60% aura applies
+
+        apply_auras(&mut allied_team, &mut enemy_team);
+        let enemy_powers: Vec<i32> = enemy_team.iter().map(|character| character.power()).collect();
+
+        assert_eq!(enemy_powers, expected_enemy_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_stacking_up_to() {
+        let expected_powers: Vec<i32> = vec![100; 4]; // +60% and +40%
+
+        assert_eq!(stacking_powers(Stacking::UpTo(2)), expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_stacking_always() {
+        let expected_powers: Vec<i32> = vec![110; 4]; // +20%, +60%, and +40%
+
+        assert_eq!(stacking_powers(Stacking::Always), expected_powers);
+    }
+
+    #[test]
+    fn test_apply_auras_enemies() {
+        let expected_allied_powers: Vec<i32> = vec![100, 100, 100, 100];
+        let expected_enemy_powers: Vec<i32> = vec![25, 25, 25, 25];
+
+        let (allied_powers, enemy_powers) = aura_powers(Aura::new(Statistic::Power, AuraTarget::Enemies, -0.5));
+
+        assert_eq!(allied_powers, expected_allied_powers);
+        assert_eq!(enemy_powers, expected_enemy_powers);
+    }
+
+    // Positioning tests
+    #[test]
+    fn test_advance_back_row() {
+        let mut squad: Vec<Character> = test_squad();
+        squad[configs::FRONT_LEFT].health = 0;
+        squad[configs::FRONT_RIGHT].health = 0;
+        let expected_swaps: Vec<Swap> = vec![(configs::FRONT_LEFT, configs::BACK_LEFT),
+            (configs::FRONT_RIGHT, configs::BACK_RIGHT)];
+
+        let swaps: Vec<Swap> = advance_back_row(&mut squad);
+
+        assert_eq!(swaps, expected_swaps);
+        assert_eq!(squad[configs::FRONT_LEFT].name, "Mage");
+        assert_eq!(squad[configs::FRONT_RIGHT].name, "Archer");
+        assert!(squad[configs::BACK_LEFT].is_dead());
+    }
+
+    #[test]
+    fn test_advance_back_row_keeps_fallen_dead() {
+        let mut squad: Vec<Character> = test_squad();
+        for character in squad.iter_mut() {
+            character.formation = vec![PositionModifier::new(Statistic::Health, Placement::BackRow, 0.5)];
+        }
+        squad[configs::FRONT_LEFT].health = -10;
+        squad[configs::FRONT_RIGHT].health = -10;
+
+        advance_back_row(&mut squad);
+
+        assert!(squad[configs::BACK_LEFT].is_dead());
+        assert!(squad[configs::BACK_RIGHT].is_dead());
+    }
+
+    #[test]
+    fn test_advance_back_row_front_alive() {
+        let mut squad: Vec<Character> = test_squad();
+        squad[configs::FRONT_LEFT].health = 0;
+
+        let swaps: Vec<Swap> = advance_back_row(&mut squad);
+
+        assert!(swaps.is_empty());
+        assert_eq!(squad[configs::FRONT_LEFT].name, "Tank");
+    }
+
+    #[test]
+    fn test_push_to_front() {
+        let mut squad: Vec<Character> = test_squad();
+        squad[configs::BACK_RIGHT].formation = vec![PositionModifier::new(Statistic::Dr, Placement::FrontRow, 0.2)];
+        const EXPECTED_DR: f64 = 0.2;
+
+        let swap: Option<Swap> = push_to_front(&mut squad, configs::BACK_RIGHT);
+
+        assert_eq!(swap, Some((configs::BACK_RIGHT, configs::FRONT_RIGHT)));
+        assert_eq!(squad[configs::FRONT_RIGHT].name, "Archer");
+        assert_eq!(squad[configs::FRONT_RIGHT].dr(), EXPECTED_DR);
+        assert_eq!(push_to_front(&mut squad, configs::FRONT_LEFT), None);
+    }
+
+    #[test]
+    fn test_retreat_to_back() {
+        let mut squad: Vec<Character> = test_squad();
+
+        let swap: Option<Swap> = retreat_to_back(&mut squad, configs::FRONT_LEFT);
+
+        assert_eq!(swap, Some((configs::FRONT_LEFT, configs::BACK_LEFT)));
+        assert_eq!(squad[configs::BACK_LEFT].name, "Tank");
+        assert_eq!(retreat_to_back(&mut squad, configs::BACK_LEFT), None);
+    }
+
+    #[test]
+    fn test_substitute_reserves() {
+        let mut squad: Vec<Character> = test_squad();
+        squad[configs::FRONT_RIGHT].health = 0;
+        squad[configs::BACK_LEFT].health = 0;
+        let mut reserve = Character::builder("Reserve").class("Warrior").speed(0).power(50).critical_chance(0.1)
+            .auras(vec![Aura::new(Statistic::Power, AuraTarget::Itself, 0.2)]).build();
+        reserve.formation = vec![PositionModifier::new(Statistic::Dr, Placement::FrontRow, 0.2)];
+        squad[configs::FRONT_LEFT].auras = vec![Aura::new(Statistic::Power, AuraTarget::Allies, 0.1)];
+        let mut reserves: Vec<Character> = vec![reserve];
+        let mut enemy_team: Vec<Character> = test_squad();
+        enemy_team[configs::FRONT_LEFT].auras = vec![Aura::new(Statistic::Health, AuraTarget::Enemies, -0.1)];
+        let mut fallen: Vec<Character> = Vec::new();
+        const EXPECTED_POWER: i32 = 65; // its own aura and the Tank's
+        const EXPECTED_DR: f64 = 0.2;
+        const EXPECTED_HEALTH_MAX: i32 = 90; // the enemy debuff
+
+        let filled: Vec<usize> = substitute_reserves(&mut squad, &mut reserves, &enemy_team, &mut fallen);
+
+        assert_eq!(filled, vec![configs::FRONT_RIGHT]);
+        assert!(reserves.is_empty());
+        assert_eq!(fallen.len(), 1);
+        assert_eq!(fallen[0].name, "Fighter");
+        assert_eq!(squad[configs::FRONT_RIGHT].name, "Reserve");
+        assert_eq!(squad[configs::FRONT_RIGHT].power(), EXPECTED_POWER);
+        assert_eq!(squad[configs::FRONT_RIGHT].dr(), EXPECTED_DR);
+        assert_eq!(squad[configs::FRONT_RIGHT].health_max(), EXPECTED_HEALTH_MAX);
+        assert!(squad[configs::BACK_LEFT].is_dead());
+    }
+}
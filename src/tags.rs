@@ -0,0 +1,91 @@
+/*
+Tags are the traits a character can have. Some change how targeting works; the rest have no effect
+of their own, but auras (allies_with_tag) and abilities (target_has_tag) can single them out, e.g. a
+priest whose heals only reach undead allies. In YAML they're written in lowercase, e.g.
+`tags: ["flanker"]`; characters without traits can leave the list empty or out entirely.
+*/
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tag {
+    Flanker,
+    Taunt,
+    Stealth,
+    Undead,
+    Flying,
+    Beast,
+    Boss
+}
+
+impl Tag {
+    // What the tag does on its own (None for traits that only matter to auras and abilities)
+    pub fn effect(&self) -> Option<&'static str> {
+        match self {
+            Tag::Flanker => Some("attacks the back row first"),
+            Tag::Taunt => Some("draws single-target attacks"),
+            Tag::Stealth => Some("can't be attacked while anyone else can"),
+            Tag::Undead | Tag::Flying | Tag::Beast | Tag::Boss => None
+        }
+    }
+
+    // e.g. "flanker (attacks the back row first)" or "undead"
+    pub fn describe(&self) -> String {
+        match self.effect() {
+            Some(effect) => format!("{} ({})", self, effect),
+            None => self.to_string()
+        }
+    }
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name: &str = match self {
+            Tag::Flanker => "flanker",
+            Tag::Taunt => "taunt",
+            Tag::Stealth => "stealth",
+            Tag::Undead => "undead",
+            Tag::Flying => "flying",
+            Tag::Beast => "beast",
+            Tag::Boss => "boss"
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_tags() {
+        let tags: Vec<Tag> = serde_yaml::from_str("- \"flanker\"\n- \"undead\"").unwrap();
+
+        assert_eq!(tags, vec![Tag::Flanker, Tag::Undead]);
+    }
+
+    #[test]
+    fn test_deserialize_unknown_tag() {
+        assert!(serde_yaml::from_str::<Vec<Tag>>("- \"null\"").is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(Tag::Flanker.describe(), "flanker (attacks the back row first)");
+        assert_eq!(Tag::Undead.describe(), "undead");
+    }
+
+    #[test]
+    fn test_display_matches_yaml() {
+        for tag in [Tag::Flanker, Tag::Taunt, Tag::Stealth, Tag::Undead, Tag::Flying, Tag::Beast, Tag::Boss] {
+            let yaml: String = serde_yaml::to_string(&tag).unwrap();
+
+            assert!(yaml.contains(&tag.to_string()));
+        }
+    }
+}
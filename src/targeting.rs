@@ -8,34 +8,187 @@ Targeting:
         - Row attacker
         - Column attacker
         - All attacker
+    - Guardians can intercept single-target attacks aimed at the positions they protect (their ward)
+    - Taunting enemies draw single-target attacks; stealthed enemies are skipped while possible
+    - Every targeting decision records the reasons behind it (for tests and debugging)
 */
 
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+
+use crate::character::Character;
 use crate::configs;
+use crate::tags::Tag;
+
+// The positions a guardian protects. In YAML: `ward: "adjacent"` or `ward: "back row"`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Ward {
+    #[serde(rename = "adjacent")]
+    Adjacent, // positions next to the guardian
+    #[serde(rename = "back row")]
+    BackRow
+}
+
+// Used by Character struct
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Guard {
+    pub ward: Ward,
+    pub chance: f64 // chance to intercept each eligible attack
+}
+
+impl Guard {
+    pub fn new(ward: Ward, chance: f64) -> Guard {
+        Guard {ward, chance}
+    }
+
+    // Check whether a guardian standing at guard_position protects the character at ward_position
+    pub fn covers(&self, guard_position: usize, ward_position: usize) -> bool {
+        if guard_position == ward_position {
+            return false
+        }
+        match self.ward {
+            Ward::Adjacent => is_adjacent(guard_position, ward_position),
+            Ward::BackRow => is_back_row(ward_position)
+        }
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Position helpers (the pet slot belongs to no row or column)
+pub fn is_front_row(position: usize) -> bool {
+    position == configs::FRONT_LEFT || position == configs::FRONT_RIGHT
+}
+
+pub fn is_back_row(position: usize) -> bool {
+    position == configs::BACK_LEFT || position == configs::BACK_RIGHT
+}
+
+pub fn is_left_column(position: usize) -> bool {
+    position == configs::FRONT_LEFT || position == configs::BACK_LEFT
+}
 
-pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, attacker_tags: &Vec<String>) -> Vec<bool> {
+pub fn is_right_column(position: usize) -> bool {
+    position == configs::FRONT_RIGHT || position == configs::BACK_RIGHT
+}
+
+// Adjacent positions share a row or a column (no diagonals)
+pub fn is_adjacent(position_a: usize, position_b: usize) -> bool {
+    if position_a == position_b {
+        return false
+    }
+    (is_front_row(position_a) && is_front_row(position_b))
+        || (is_back_row(position_a) && is_back_row(position_b))
+        || (is_left_column(position_a) && is_left_column(position_b))
+        || (is_right_column(position_a) && is_right_column(position_b))
+}
+
+// Only single-target attacks can be intercepted by guardians
+pub fn is_single_target(attack_type: &str) -> bool {
+    !(attack_type == "row" || attack_type == "column" || attack_type == "all")
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Why targets were chosen, in the order the rules were applied
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TargetReason {
+    FrontRowFirst, // default: attack the front row first
+    FlankerRule, // flankers attack the back row first
+    FrontRowEmptyFallback, // nobody left in the front row, so attack the back row
+    BackRowEmptyFallback, // (flankers) nobody left in the back row, so attack the front row
+    LeftColumnFirst, // column attackers hit the left column first
+    LeftColumnEmptyFallback, // nobody left in the left column, so attack the right column
+    AttackAll, // attacks every living enemy
+    TauntOverride, // a taunting enemy draws single-target attacks
+    StealthSkip // stealthed enemies are skipped while anyone else can be attacked
+}
+
+impl fmt::Display for TargetReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description: &str = match self {
+            TargetReason::FrontRowFirst => "front row is targeted first",
+            TargetReason::FlankerRule => "flanker targets the back row first",
+            TargetReason::FrontRowEmptyFallback => "front row is empty, falling back to the back row",
+            TargetReason::BackRowEmptyFallback => "back row is empty, falling back to the front row",
+            TargetReason::LeftColumnFirst => "left column is targeted first",
+            TargetReason::LeftColumnEmptyFallback => "left column is empty, falling back to the right column",
+            TargetReason::AttackAll => "attacks all living enemies",
+            TargetReason::TauntOverride => "a taunting enemy draws the attack",
+            TargetReason::StealthSkip => "stealthed enemies are skipped"
+        };
+        write!(f, "{}", description)
+    }
+}
+
+// Targets chosen by the targeting rules along with the reasons for the choice
+#[derive(Clone, Debug, PartialEq)]
+pub struct TargetingDecision {
+    pub targets: Vec<bool>,
+    pub reasons: Vec<TargetReason>
+}
+
+impl TargetingDecision {
+    pub fn describe(&self) -> String {
+        let reasons: Vec<String> = self.reasons.iter().map(|reason| reason.to_string()).collect();
+        reasons.join("; ")
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+// Explain who an attacker would hit given the current state of the enemy squad
+pub fn explain_attack(attacker: &Character, enemy_team: &[Character]) -> TargetingDecision {
+    let alive_targets: Vec<bool> = enemy_team.iter().map(|character| !character.is_dead()).collect();
+    let enemy_tags: Vec<Vec<Tag>> = enemy_team.iter().map(|character| character.tags.clone()).collect();
+    explain_targets(&attacker.attack_type, alive_targets, &attacker.tags, &enemy_tags)
+}
+
+pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, attacker_tags: &Vec<Tag>) -> Vec<bool> {
+    explain_targets(attack_type, alive_targets, attacker_tags, &[]).targets
+}
+
+// Enemy tags are optional (an empty slice means no taunting or stealthed enemies)
+pub fn explain_targets(attack_type: &str, alive_targets: Vec<bool>, attacker_tags: &[Tag],
+    enemy_tags: &[Vec<Tag>]) -> TargetingDecision {
     let num_targets = alive_targets.len();
     let mut targets: Vec<bool> = Vec::with_capacity(configs::TEAM_SIZE);
+    let mut reasons: Vec<TargetReason> = Vec::new();
+    let enemy_has_tag = |index: usize, tag: Tag| {
+        enemy_tags.get(index).is_some_and(|tags| tags.contains(&tag))
+    };
     if attack_type == "row" {
         // Initialize variables to fill inside of if/else statement
         let pos_left_primary: usize;
         let pos_right_primary: usize;
         let pos_left_backup: usize;
         let pos_right_backup: usize;
+        let fallback_reason: TargetReason;
         // flankers target back row first
-        if attacker_tags.contains(&"flanker".to_string()) {
+        if attacker_tags.contains(&Tag::Flanker) {
             pos_left_primary = configs::BACK_LEFT;
             pos_right_primary = configs::BACK_RIGHT;
             pos_left_backup = configs::FRONT_LEFT;
             pos_right_backup = configs::FRONT_RIGHT;
+            reasons.push(TargetReason::FlankerRule);
+            fallback_reason = TargetReason::BackRowEmptyFallback;
         } else { // everyone else targets front row first
             pos_left_primary = configs::FRONT_LEFT;
             pos_right_primary = configs::FRONT_RIGHT;
             pos_left_backup = configs::BACK_LEFT;
             pos_right_backup = configs::BACK_RIGHT;
+            reasons.push(TargetReason::FrontRowFirst);
+            fallback_reason = TargetReason::FrontRowEmptyFallback;
+        }
+        let primary_alive: bool = alive_targets[pos_left_primary] || alive_targets[pos_right_primary];
+        if !primary_alive {
+            reasons.push(fallback_reason);
         }
         // Loop across targets to assign attacks
         for i in 0..num_targets {
-            if alive_targets[pos_left_primary] || alive_targets[pos_right_primary] {
+            if primary_alive {
                 if alive_targets[i] && (i == pos_left_primary || i == pos_right_primary) {
                     targets.push(true);
                 } else {
@@ -49,11 +202,15 @@ pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, a
                 }
             }
         }
-        return targets;
     } else if attack_type == "column" {
         // Attacks left column by default -- TODO -> update to use rand to 50/50 left and right
+        reasons.push(TargetReason::LeftColumnFirst);
+        let left_alive: bool = alive_targets[configs::FRONT_LEFT] || alive_targets[configs::BACK_LEFT];
+        if !left_alive {
+            reasons.push(TargetReason::LeftColumnEmptyFallback);
+        }
         for i in 0..num_targets {
-            if alive_targets[configs::FRONT_LEFT] || alive_targets[configs::BACK_LEFT] { // target left column if alive
+            if left_alive { // target left column if alive
                 if alive_targets[i] && (i == configs::FRONT_LEFT || i == configs::BACK_LEFT) { // if alive and in left column
                     targets.push(true);
                 } else {
@@ -67,8 +224,8 @@ pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, a
                 }
             }
         }
-        return targets;
     } else if attack_type == "all" {
+        reasons.push(TargetReason::AttackAll);
         for i in 0..num_targets {
             if alive_targets[i] { // if alive, then attack
                 targets.push(true);
@@ -76,33 +233,52 @@ pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, a
                 targets.push(false);
             }
         }
-        return targets;
     } else { // Anything else gets thrown to single attacker type
+        // Stealthed enemies can't be picked out while anyone else is available
+        let mut candidates: Vec<bool> = alive_targets.clone();
+        let visible: Vec<bool> = (0..num_targets).map(|i| alive_targets[i] && !enemy_has_tag(i, Tag::Stealth)).collect();
+        if visible != alive_targets && visible.contains(&true) {
+            reasons.push(TargetReason::StealthSkip);
+            candidates = visible;
+        }
+        // Taunting enemies draw the attack regardless of position
+        if let Some(taunter) = (0..num_targets).find(|i| candidates[*i] && enemy_has_tag(*i, Tag::Taunt)) {
+            reasons.push(TargetReason::TauntOverride);
+            targets = (0..num_targets).map(|i| i == taunter).collect();
+            return TargetingDecision {targets, reasons};
+        }
         // Attacks front left by default -- TODO -> update to use rand to 50/50 left and right
         let mut target_not_selected: bool = true; // once a single target has been chosen will change to true
-        if attacker_tags.contains(&"flanker".to_string()) { // flankers target back row first
+        if attacker_tags.contains(&Tag::Flanker) { // flankers target back row first
+            reasons.push(TargetReason::FlankerRule);
             for i in (0..num_targets).rev() {
-                if alive_targets[i] && target_not_selected {
+                if candidates[i] && target_not_selected {
                     targets.push(true);
                     target_not_selected = false;
+                    if !is_back_row(i) {
+                        reasons.push(TargetReason::BackRowEmptyFallback);
+                    }
                 } else {
                     targets.push(false);
                 }
             }
             targets.reverse();
-            return targets;
         } else { // normal single attackers target front row first
-            for i in 0..num_targets {
-                if alive_targets[i] && target_not_selected {
+            reasons.push(TargetReason::FrontRowFirst);
+            for (i, candidate) in candidates.iter().enumerate() {
+                if *candidate && target_not_selected {
                     targets.push(true);
                     target_not_selected = false;
+                    if !is_front_row(i) {
+                        reasons.push(TargetReason::FrontRowEmptyFallback);
+                    }
                 } else {
                     targets.push(false);
                 }
             }
-            return targets;
         }
     }
+    TargetingDecision {targets, reasons}
 }
 
 /* --------------------------------------------------------------------------------------------- */
@@ -110,6 +286,7 @@ pub fn attack_type_to_coordinates(attack_type: &str, alive_targets: Vec<bool>, a
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_helpers::test_squad;
 
     // Target generation tests
 
@@ -119,7 +296,7 @@ mod tests {
         let attack_type: &str = "single";
         let alive_targets: Vec<bool> = vec![true, true, false, true];
         let expected_targets: Vec<bool> = vec![true, false, false, false];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -131,7 +308,7 @@ mod tests {
         let attack_type: &str = "single";
         let alive_targets: Vec<bool> = vec![false, false, false, true];
         let expected_targets: Vec<bool> = vec![false, false, false, true];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -143,7 +320,7 @@ mod tests {
         let attack_type: &str = "single";
         let alive_targets: Vec<bool> = vec![true, false, true, false];
         let expected_targets: Vec<bool> = vec![false, false, true, false];
-        let tags: Vec<String> = vec!["flanker".to_string()];
+        let tags: Vec<Tag> = vec![Tag::Flanker];
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -155,7 +332,7 @@ mod tests {
         let attack_type: &str = "row";
         let alive_targets: Vec<bool> = vec![true, true, false, true];
         let expected_targets: Vec<bool> = vec![true, true, false, false];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -167,7 +344,7 @@ mod tests {
         let attack_type: &str = "row";
         let alive_targets: Vec<bool> = vec![false, false, true, true];
         let expected_targets: Vec<bool> = vec![false, false, true, true];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -179,7 +356,7 @@ mod tests {
         let attack_type: &str = "row";
         let alive_targets: Vec<bool> = vec![false, true, false, true];
         let expected_targets: Vec<bool> = vec![false, true, false, false];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -191,7 +368,7 @@ mod tests {
         let attack_type: &str = "row";
         let alive_targets: Vec<bool> = vec![false, false, true, false];
         let expected_targets: Vec<bool> = vec![false, false, true, false];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -203,7 +380,7 @@ mod tests {
         let attack_type: &str = "row";
         let alive_targets: Vec<bool> = vec![true, false, true, true];
         let expected_targets: Vec<bool> = vec![false, false, true, true];
-        let tags: Vec<String> = vec!["flanker".to_string()];
+        let tags: Vec<Tag> = vec![Tag::Flanker];
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -215,7 +392,7 @@ mod tests {
         let attack_type: &str = "column";
         let alive_targets: Vec<bool> = vec![true, true, true, false];
         let expected_targets: Vec<bool> = vec![true, false, true, false];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -227,7 +404,7 @@ mod tests {
         let attack_type: &str = "column";
         let alive_targets: Vec<bool> = vec![false, true, false, true];
         let expected_targets: Vec<bool> = vec![false, true, false, true];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
@@ -239,9 +416,132 @@ mod tests {
         let attack_type: &str = "all";
         let alive_targets: Vec<bool> = vec![true, true, false, true];
         let expected_targets: Vec<bool> = vec![true, true, false, true];
-        let tags: Vec<String> = vec!["null".to_string()];
+        let tags: Vec<Tag> = Vec::new();
 
         let targets: Vec<bool> = attack_type_to_coordinates(attack_type, alive_targets, &tags);
         assert_eq!(targets, expected_targets);
     }
+
+    // Targeting explanation tests
+    #[test]
+    fn test_explain_targets_flanker() {
+        let alive_targets: Vec<bool> = vec![true, true, true, false];
+        let tags: Vec<Tag> = vec![Tag::Flanker];
+        let expected_targets: Vec<bool> = vec![false, false, true, false];
+        let expected_reasons: Vec<TargetReason> = vec![TargetReason::FlankerRule];
+
+        let decision: TargetingDecision = explain_targets("single", alive_targets, &tags, &[]);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert_eq!(decision.reasons, expected_reasons);
+    }
+
+    #[test]
+    fn test_explain_targets_front_row_empty() {
+        let alive_targets: Vec<bool> = vec![false, false, true, true];
+        let tags: Vec<Tag> = Vec::new();
+        let expected_targets: Vec<bool> = vec![false, false, true, true];
+        let expected_reasons: Vec<TargetReason> = vec![TargetReason::FrontRowFirst,
+            TargetReason::FrontRowEmptyFallback];
+
+        let decision: TargetingDecision = explain_targets("row", alive_targets, &tags, &[]);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert_eq!(decision.reasons, expected_reasons);
+    }
+
+    #[test]
+    fn test_explain_targets_taunt() {
+        let alive_targets: Vec<bool> = vec![true, true, true, true];
+        let tags: Vec<Tag> = vec![Tag::Flanker];
+        let enemy_tags: Vec<Vec<Tag>> = vec![Vec::new(), vec![Tag::Taunt], Vec::new(), Vec::new()];
+        let expected_targets: Vec<bool> = vec![false, true, false, false];
+        let expected_reasons: Vec<TargetReason> = vec![TargetReason::TauntOverride];
+
+        let decision: TargetingDecision = explain_targets("single", alive_targets, &tags, &enemy_tags);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert_eq!(decision.reasons, expected_reasons);
+    }
+
+    #[test]
+    fn test_explain_targets_stealth() {
+        let alive_targets: Vec<bool> = vec![true, true, false, false];
+        let tags: Vec<Tag> = Vec::new();
+        let enemy_tags: Vec<Vec<Tag>> = vec![vec![Tag::Stealth], Vec::new(), Vec::new(), Vec::new()];
+        let expected_targets: Vec<bool> = vec![false, true, false, false];
+        let expected_reasons: Vec<TargetReason> = vec![TargetReason::StealthSkip, TargetReason::FrontRowFirst];
+
+        let decision: TargetingDecision = explain_targets("single", alive_targets, &tags, &enemy_tags);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert_eq!(decision.reasons, expected_reasons);
+    }
+
+    #[test]
+    fn test_explain_targets_stealth_only_targets() {
+        let alive_targets: Vec<bool> = vec![true, false, false, false];
+        let tags: Vec<Tag> = Vec::new();
+        let enemy_tags: Vec<Vec<Tag>> = vec![vec![Tag::Stealth], Vec::new(), Vec::new(), Vec::new()];
+        let expected_targets: Vec<bool> = vec![true, false, false, false];
+
+        let decision: TargetingDecision = explain_targets("single", alive_targets, &tags, &enemy_tags);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert!(!decision.reasons.contains(&TargetReason::StealthSkip));
+    }
+
+    #[test]
+    fn test_explain_attack() {
+        let attacker = Character::builder("Rogue").class("Rogue").speed(0).tags(vec![Tag::Flanker]).build();
+        let mut enemy_team: Vec<Character> = test_squad();
+        enemy_team[configs::BACK_LEFT].health = 0;
+        enemy_team[configs::BACK_RIGHT].health = 0;
+        let expected_targets: Vec<bool> = vec![false, true, false, false];
+        let expected_reasons: Vec<TargetReason> = vec![TargetReason::FlankerRule, TargetReason::BackRowEmptyFallback];
+
+        let decision: TargetingDecision = explain_attack(&attacker, &enemy_team);
+
+        assert_eq!(decision.targets, expected_targets);
+        assert_eq!(decision.reasons, expected_reasons);
+        assert_eq!(decision.describe(), "flanker targets the back row first; back row is empty, falling back to the front row");
+    }
+
+    // Position tests
+    #[test]
+    fn test_is_adjacent() {
+        assert!(is_adjacent(configs::FRONT_LEFT, configs::FRONT_RIGHT));
+        assert!(is_adjacent(configs::FRONT_LEFT, configs::BACK_LEFT));
+        assert!(!is_adjacent(configs::FRONT_LEFT, configs::BACK_RIGHT));
+        assert!(!is_adjacent(configs::FRONT_LEFT, configs::FRONT_LEFT));
+        assert!(!is_adjacent(configs::FRONT_LEFT, configs::PET));
+    }
+
+    // Guard tests
+    #[test]
+    fn test_guard_covers_adjacent() {
+        let guard = Guard::new(Ward::Adjacent, 0.5);
+
+        assert!(guard.covers(configs::FRONT_LEFT, configs::BACK_LEFT));
+        assert!(!guard.covers(configs::FRONT_LEFT, configs::BACK_RIGHT));
+        assert!(!guard.covers(configs::FRONT_LEFT, configs::FRONT_LEFT));
+    }
+
+    #[test]
+    fn test_guard_covers_back_row() {
+        let guard = Guard::new(Ward::BackRow, 0.5);
+
+        assert!(guard.covers(configs::FRONT_LEFT, configs::BACK_LEFT));
+        assert!(guard.covers(configs::FRONT_LEFT, configs::BACK_RIGHT));
+        assert!(!guard.covers(configs::FRONT_LEFT, configs::FRONT_RIGHT));
+        assert!(!guard.covers(configs::BACK_LEFT, configs::BACK_LEFT));
+    }
+
+    #[test]
+    fn test_deserialize_guard() {
+        let guard: Guard = serde_yaml::from_str("ward: \"back row\"\nchance: 0.5").unwrap();
+
+        assert_eq!(guard, Guard::new(Ward::BackRow, 0.5));
+        assert!(serde_yaml::from_str::<Guard>("ward: \"front row\"\nchance: 0.5").is_err());
+    }
 }
@@ -0,0 +1,13 @@
+/*
+Fixtures shared by the tests of several modules (compiled for tests only).
+*/
+
+use crate::character::Character;
+
+// A plain four-member squad (Tank, Fighter, Mage, Archer): level 1 warriors with power 50, speed 0,
+// and the builder's defaults otherwise (no guards, tags, auras, abilities, or damage reduction)
+pub fn test_squad() -> Vec<Character> {
+    ["Tank", "Fighter", "Mage", "Archer"].iter()
+        .map(|name| Character::builder(name).class("Warrior").speed(0).power(50).build())
+        .collect()
+}
@@ -0,0 +1,338 @@
+/*
+Validation of loaded characters and items. Every problem in a file is collected into one report, so a designer
+fixing data sees all of them at once instead of one per run. Warnings are fixed or tolerated by the
+game; errors stop the character (or item) from loading.
+
+File structure:
+- Known attack types and trigger events
+- Severity (enum), Issue and ValidationReport (structs)
+- validate_character, and the ability and aura checks it shares with validate_item
+- Tests
+*/
+
+use crate::character::Character;
+use crate::configs;
+use crate::error::GameError;
+use crate::error::GameResult;
+use crate::items::Item;
+use crate::modifiers::Ability;
+use crate::modifiers::AbilityTarget;
+use crate::modifiers::Apply;
+use crate::modifiers::Aura;
+use crate::modifiers::EffectKind;
+use crate::modifiers::ModifierMode;
+use crate::modifiers::Movement;
+use crate::modifiers::Statistic;
+
+pub const ATTACK_TYPES: [&str; 4] = ["single", "row", "column", "all"];
+// "killed" and "died" are reserved in combat.rs but never fire yet, so abilities can't use them
+pub const TRIGGER_EVENTS: [&str; 3] = ["attack", "attacked", "battle_start"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Warning, // the game can carry on (possibly after fixing the value)
+    Error // the character can't be used
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub message: String
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ValidationReport {
+    pub path: String,
+    pub issues: Vec<Issue>
+}
+
+impl ValidationReport {
+    pub fn new(path: &str) -> ValidationReport {
+        ValidationReport {path: path.to_string(), issues: Vec::new()}
+    }
+
+    pub fn warn(&mut self, message: String) {
+        self.issues.push(Issue {severity: Severity::Warning, message});
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.issues.push(Issue {severity: Severity::Error, message});
+    }
+
+    fn messages(&self, severity: Severity) -> Vec<&str> {
+        self.issues.iter().filter(|issue| issue.severity == severity).map(|issue| issue.message.as_str()).collect()
+    }
+
+    pub fn warnings(&self) -> Vec<&str> {
+        self.messages(Severity::Warning)
+    }
+
+    pub fn errors(&self) -> Vec<&str> {
+        self.messages(Severity::Error)
+    }
+
+    pub fn print_warnings(&self) {
+        for warning in self.warnings() {
+            println!("WARNING: data error in {}: {}", self.path, warning);
+        }
+    }
+
+    // All errors become a single GameError, so the player sees the whole list
+    pub fn into_result(self) -> GameResult<()> {
+        let errors: Vec<&str> = self.errors();
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(GameError::validation(&self.path, &errors.join("; ")))
+    }
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+pub fn validate_character(character: &Character, path: &str) -> ValidationReport {
+    let mut report: ValidationReport = ValidationReport::new(path);
+    let stats = &character.base;
+
+    if character.name.trim().is_empty() {
+        report.error("name is empty".to_string());
+    }
+    // Statistics
+    if stats.health_max <= 0 {
+        report.error(format!("maximum health ({}) must be positive", stats.health_max));
+    }
+    if character.health < 0 {
+        report.error(format!("current health ({}) is negative", character.health));
+    } else if character.health > stats.health_max {
+        report.warn(format!("current health ({}) > maximum health ({}). Overriding automatically.",
+            character.health, stats.health_max));
+    }
+    if stats.power < 0 {
+        report.error(format!("power ({}) is negative", stats.power));
+    }
+    if stats.dt < 0 {
+        report.error(format!("damage threshold ({}) is negative", stats.dt));
+    }
+    if !(0.0..=1.0).contains(&stats.critical_chance) {
+        report.error(format!("critical chance ({}) must be between 0 and 1", stats.critical_chance));
+    } else if stats.critical_chance > configs::CRITICAL_CHANCE_CAP {
+        report.warn(format!("critical chance ({}) is above the cap ({}) and will be capped",
+            stats.critical_chance, configs::CRITICAL_CHANCE_CAP));
+    }
+    if !(0.0..=1.0).contains(&stats.dr) {
+        report.error(format!("damage reduction ({}) must be between 0 and 1", stats.dr));
+    } else if stats.dr > configs::MAXIUMUM_DAMAGE_RESIST {
+        report.warn(format!("damage reduction ({}) is above the cap ({}) and will be capped",
+            stats.dr, configs::MAXIUMUM_DAMAGE_RESIST));
+    }
+    // Names that must match what the game knows about
+    if !ATTACK_TYPES.contains(&character.attack_type.as_str()) {
+        report.error(format!("unknown attack type \"{}\" (expected one of {})", character.attack_type,
+            ATTACK_TYPES.join(", ")));
+    }
+    validate_abilities(&mut report, &character.abilities);
+    validate_auras(&mut report, &character.auras);
+    if let Some(guard) = &character.guard {
+        if !(0.0..=1.0).contains(&guard.chance) {
+            report.error(format!("guard chance ({}) must be between 0 and 1", guard.chance));
+        }
+    }
+    report
+}
+
+// Items grant abilities and auras too, so these checks are shared with validate_item
+fn validate_abilities(report: &mut ValidationReport, abilities: &[Ability]) {
+    for ability in abilities.iter() {
+        if !TRIGGER_EVENTS.contains(&ability.trigger_event.as_str()) {
+            report.error(format!("ability \"{}\" has unknown trigger event \"{}\" (expected one of {})",
+                ability.name, ability.trigger_event, TRIGGER_EVENTS.join(", ")));
+        }
+        if !(0.0..=1.0).contains(&ability.chance) {
+            report.error(format!("ability \"{}\" has chance ({}) outside 0 to 1", ability.name, ability.chance));
+        }
+        for effect in ability.effects.iter() {
+            if let Some(trigger) = effect.target.trigger() {
+                if ability.trigger_event != trigger {
+                    report.error(format!("ability \"{}\" targets {:?} but only \"{}\" triggers have one",
+                        ability.name, effect.target, trigger));
+                }
+            }
+            let movement_target: bool = match effect.movement {
+                Some(Movement::Push) => matches!(effect.target, AbilityTarget::Attacked | AbilityTarget::Attacker),
+                Some(Movement::Retreat) => effect.target == AbilityTarget::Itself,
+                None => true
+            };
+            if !movement_target {
+                report.error(format!("ability \"{}\" can't {:?} {:?} (push the attacked or attacker, retreat self)",
+                    ability.name, effect.movement.unwrap_or(Movement::Push), effect.target));
+            }
+            if effect.kind == EffectKind::Modifier && effect.statistic.is_none() && effect.movement.is_none() {
+                report.error(format!("ability \"{}\" has a modifier effect without a statistic", ability.name));
+            }
+            // Maximum health has its own effect kind, so there's only one way to change it
+            if effect.kind == EffectKind::Modifier && effect.statistic == Some(Statistic::Health) {
+                report.error(format!("ability \"{}\" has a health modifier effect (use a max_health effect)",
+                    ability.name));
+            }
+            // Heals, damage, and maximum health changes have a fixed statistic and never move anyone
+            if effect.kind != EffectKind::Modifier && (effect.statistic.is_some() || effect.movement.is_some()) {
+                report.error(format!("ability \"{}\" has a {:?} effect with a statistic or movement it would ignore",
+                    ability.name, effect.kind));
+            }
+            if effect.statistic.is_some() && effect.movement.is_some() {
+                report.error(format!("ability \"{}\" has an effect with both a statistic and a movement",
+                    ability.name));
+            }
+        }
+    }
+}
+
+// Auras that would wipe out a statistic for everyone they reach
+fn validate_auras(report: &mut ValidationReport, auras: &[Aura]) {
+    for aura in auras.iter() {
+        let zeroes_statistic: bool = match aura.mode(aura.statistic) {
+            ModifierMode::PercentOfBase | ModifierMode::PercentOfCurrent => aura.value <= -1.0,
+            ModifierMode::Set => aura.value <= 0.0 && matches!(aura.statistic, Statistic::Health | Statistic::Power),
+            ModifierMode::Flat => false
+        };
+        if zeroes_statistic {
+            report.error(format!("{:?} aura with value {} would reduce {:?} to zero", aura.target, aura.value,
+                aura.statistic));
+        }
+    }
+}
+
+pub fn validate_item(item: &Item, path: &str) -> ValidationReport {
+    let mut report: ValidationReport = ValidationReport::new(path);
+    if item.name.trim().is_empty() {
+        report.error("name is empty".to_string());
+    }
+    validate_abilities(&mut report, &item.abilities);
+    validate_auras(&mut report, &item.auras);
+    report
+}
+
+/* --------------------------------------------------------------------------------------------- */
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Slot;
+    use crate::modifiers::AuraTarget;
+    use crate::tags::Tag;
+
+    fn valid_character() -> Character {
+        Character::builder("Dave").class("Warrior").power(50).critical_chance(0.1).tags(vec![Tag::Flanker])
+            .auras(vec![Aura::new(Statistic::Power, AuraTarget::Allies, 0.2)])
+            .abilities(vec![Ability::new(Statistic::Speed, AbilityTarget::Itself, 1.0, "attack")]).build()
+    }
+
+    #[test]
+    fn test_validate_valid_character() {
+        let report: ValidationReport = validate_character(&valid_character(), "Dave.yml");
+
+        assert!(report.issues.is_empty());
+        assert_eq!(report.into_result(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_every_problem() {
+        let mut test_character: Character = valid_character();
+        test_character.name = " ".to_string();
+        test_character.base.power = -5;
+        test_character.base.critical_chance = 1.5;
+        test_character.base.dr = 0.9;
+        test_character.attack_type = "cone".to_string();
+        test_character.abilities[0].trigger_event = "on_hit".to_string();
+        test_character.auras.push(Aura::new(Statistic::Health, AuraTarget::Enemies, -1.0));
+        const EXPECTED_ERRORS: usize = 6;
+        const EXPECTED_WARNINGS: usize = 1; // dr above the cap
+
+        let report: ValidationReport = validate_character(&test_character, "Dave.yml");
+
+        assert_eq!(report.errors().len(), EXPECTED_ERRORS);
+        assert_eq!(report.warnings().len(), EXPECTED_WARNINGS);
+        match report.into_result() {
+            Err(GameError::Validation {path, message}) => {
+                assert_eq!(path, "Dave.yml");
+                assert!(message.contains("name is empty"));
+                assert!(message.contains("\"on_hit\""));
+            },
+            other => panic!("expected a validation error, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_validate_modifier_effect_without_statistic() {
+        let mut test_character: Character = valid_character();
+        test_character.abilities[0].effects[0].statistic = None;
+
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_heal_effect_with_statistic() {
+        let mut test_character: Character = valid_character();
+        test_character.abilities[0].effects[0].kind = EffectKind::Heal;
+
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_target_needs_trigger() {
+        let mut test_character: Character = valid_character();
+        test_character.abilities.push(Ability::new(Statistic::Speed, AbilityTarget::Attacker, -1.0, "attack"));
+        test_character.abilities.push(Ability::new_movement(Movement::Push, AbilityTarget::Enemies, "attack"));
+
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 2);
+    }
+
+    #[test]
+    fn test_validate_unfired_trigger_event() {
+        let mut test_character: Character = valid_character();
+        test_character.abilities[0].trigger_event = "killed".to_string();
+
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_health_modifier_effect() {
+        let mut test_character: Character = valid_character();
+        test_character.abilities.push(Ability::new(Statistic::Health, AbilityTarget::Allies, 0.1, "attack"));
+
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_health_above_max_is_warning() {
+        let mut test_character: Character = valid_character();
+        test_character.health = 200;
+
+        let report: ValidationReport = validate_character(&test_character, "Dave.yml");
+
+        assert_eq!(report.warnings().len(), 1);
+        assert_eq!(report.into_result(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_zeroing_aura() {
+        let mut test_character: Character = valid_character();
+        test_character.auras = vec![Aura::new(Statistic::Speed, AuraTarget::Enemies, -3.0),
+            Aura::new(Statistic::Power, AuraTarget::Enemies, -0.5)];
+        assert!(validate_character(&test_character, "Dave.yml").issues.is_empty());
+
+        test_character.auras[1].mode = Some(ModifierMode::Set);
+        test_character.auras[1].value = 0.0;
+        assert_eq!(validate_character(&test_character, "Dave.yml").errors().len(), 1);
+    }
+
+    #[test]
+    fn test_validate_item() {
+        let mut item: Item = Item::new("Cursed Ring", Slot::Trinket);
+        item.auras = vec![Aura::new(Statistic::Power, AuraTarget::Allies, 0.1)];
+        assert!(validate_item(&item, "CursedRing.yml").issues.is_empty());
+
+        item.abilities = vec![Ability::new(Statistic::Power, AbilityTarget::Itself, 0.1, "on_hit")];
+        item.auras.push(Aura::new(Statistic::Health, AuraTarget::Enemies, -1.0));
+        assert_eq!(validate_item(&item, "CursedRing.yml").errors().len(), 2);
+    }
+}